@@ -0,0 +1,39 @@
+use crate::light::LightEnum;
+use crate::vec3::{Color, Vec3};
+use serde::{Deserialize, Serialize};
+
+pub mod environment_map;
+pub use environment_map::EnvironmentMap;
+pub mod sky;
+pub use sky::Sky;
+
+/// Radiance arriving from infinitely far away, seen by rays that leave the scene
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Background {
+    Color(Color),
+    Environment(Box<EnvironmentMap>),
+    Sky(Box<Sky>),
+}
+
+impl Background {
+    pub fn value(&self, direction: &Vec3) -> Color {
+        match self {
+            Background::Color(c) => *c,
+            Background::Environment(e) => e.value(direction),
+            Background::Sky(s) => s.value(direction),
+        }
+    }
+
+    /// Whether the background is sampled as a light, see `light`
+    pub fn is_light(&self) -> bool {
+        matches!(self, Background::Environment(_) | Background::Sky(_))
+    }
+
+    pub fn light(&self) -> Option<LightEnum> {
+        match self {
+            Background::Color(_) => None,
+            Background::Environment(e) => Some(LightEnum::Environment((**e).clone())),
+            Background::Sky(s) => Some(LightEnum::Sun((**s).clone())),
+        }
+    }
+}
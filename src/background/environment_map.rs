@@ -0,0 +1,191 @@
+use crate::light::{emission_from_infinity, EmissionSample, Light, LightSample};
+use crate::random;
+use crate::spectrum::luminance;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Light from an equirectangular HDR image (Radiance .hdr, OpenEXR, ...) surrounding
+/// the scene, with +y up. Directions are importance sampled by luminance.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    /// Linear RGB, row by row from the top
+    pixels: Vec<f32>,
+    rotation: f64,
+    intensity: f64,
+    /// Cumulative distribution over the rows, `height + 1` entries from 0 to 1
+    row_cdf: Vec<f64>,
+    /// Cumulative distribution over the pixels of each row, `width + 1` entries per row
+    column_cdf: Vec<f64>,
+    /// Sum of the weights of all pixels, 0 for a black image
+    total_weight: f64,
+}
+
+/// Index of the interval of `cdf` containing `u`
+fn find_interval(cdf: &[f64], u: f64) -> usize {
+    let i = cdf.partition_point(|&c| c <= u);
+    i.clamp(1, cdf.len() - 1) - 1
+}
+
+impl EnvironmentMap {
+    pub fn new(filename: &str) -> Self {
+        let img = image::open(filename);
+        if img.is_err() {
+            panic!("Error: Failed to open image: {}", filename);
+        }
+        let img = img.unwrap().to_rgb32f();
+        let (width, height) = (img.width() as usize, img.height() as usize);
+        Self::from_pixels(width, height, img.into_raw())
+    }
+
+    /// `pixels` holds `width * height` linear RGB triples, row by row from the top
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<f32>) -> Self {
+        assert_eq!(pixels.len(), width * height * 3);
+        let mut map = Self {
+            width,
+            height,
+            pixels,
+            rotation: 0.0,
+            intensity: 1.0,
+            row_cdf: Vec::new(),
+            column_cdf: Vec::new(),
+            total_weight: 0.0,
+        };
+        map.build_distribution();
+        map
+    }
+
+    /// Turns the map around the vertical axis by `degrees`
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = crate::degrees_to_radians(degrees);
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    fn pixel(&self, i: usize, j: usize) -> Color {
+        let index = 3 * (j * self.width + i);
+        Color::new(
+            self.pixels[index] as f64,
+            self.pixels[index + 1] as f64,
+            self.pixels[index + 2] as f64,
+        )
+    }
+
+    /// Pixels are weighted by luminance and by the solid angle their row covers
+    fn build_distribution(&mut self) {
+        let mut row_cdf = vec![0.0; self.height + 1];
+        let mut column_cdf = vec![0.0; self.height * (self.width + 1)];
+        for j in 0..self.height {
+            let sin_theta = (PI * (j as f64 + 0.5) / self.height as f64).sin();
+            let row = &mut column_cdf[j * (self.width + 1)..(j + 1) * (self.width + 1)];
+            for i in 0..self.width {
+                row[i + 1] = row[i] + luminance(&self.pixel(i, j)).max(0.0) * sin_theta;
+            }
+            let row_weight = row[self.width];
+            if row_weight > 0.0 {
+                row.iter_mut().for_each(|c| *c /= row_weight);
+            }
+            row_cdf[j + 1] = row_cdf[j] + row_weight;
+        }
+        let total_weight = row_cdf[self.height];
+        if total_weight > 0.0 {
+            row_cdf.iter_mut().for_each(|c| *c /= total_weight);
+        }
+        self.row_cdf = row_cdf;
+        self.column_cdf = column_cdf;
+        self.total_weight = total_weight;
+    }
+
+    /// Image coordinates in [0, 1]² of `direction`, and the sine of its polar angle
+    fn to_uv(&self, direction: &Vec3) -> (f64, f64, f64) {
+        let d = direction.unit();
+        let theta = d.y().clamp(-1.0, 1.0).acos();
+        let phi = d.z().atan2(d.x()) + self.rotation;
+        let u = (phi / (2.0 * PI)).rem_euclid(1.0);
+        (u, theta / PI, theta.sin())
+    }
+
+    fn direction_at(&self, u: f64, v: f64) -> Vec3 {
+        let theta = PI * v;
+        let phi = 2.0 * PI * u - self.rotation;
+        Vec3::new(
+            theta.sin() * phi.cos(),
+            theta.cos(),
+            theta.sin() * phi.sin(),
+        )
+    }
+
+    fn pixel_at(&self, u: f64, v: f64) -> (usize, usize) {
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+        (i, j)
+    }
+
+    /// Density of picking the pixel at `(i, j)` per unit area of [0, 1]²
+    fn pixel_pdf(&self, i: usize, j: usize) -> f64 {
+        let row = &self.column_cdf[j * (self.width + 1)..(j + 1) * (self.width + 1)];
+        let p_row = self.row_cdf[j + 1] - self.row_cdf[j];
+        let p_column = row[i + 1] - row[i];
+        p_row * p_column * (self.width * self.height) as f64
+    }
+
+    pub fn value(&self, direction: &Vec3) -> Color {
+        let (u, v, _) = self.to_uv(direction);
+        let (i, j) = self.pixel_at(u, v);
+        self.intensity * self.pixel(i, j)
+    }
+}
+
+impl Light for EnvironmentMap {
+    fn sample(&self, _origin: &Point3) -> Option<LightSample> {
+        if self.total_weight <= 0.0 {
+            return None;
+        }
+        let mut rng = random::rng();
+        let j = find_interval(&self.row_cdf, rng.gen_range(0.0..1.0));
+        let row = &self.column_cdf[j * (self.width + 1)..(j + 1) * (self.width + 1)];
+        let i = find_interval(row, rng.gen_range(0.0..1.0));
+
+        // Uniform within the pixel
+        let u = (i as f64 + rng.gen_range(0.0..1.0)) / self.width as f64;
+        let v = (j as f64 + rng.gen_range(0.0..1.0)) / self.height as f64;
+        let sin_theta = (PI * v).sin();
+        if sin_theta <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction: self.direction_at(u, v),
+            distance: f64::INFINITY,
+            radiance: self.intensity * self.pixel(i, j),
+            pdf: self.pixel_pdf(i, j) / (2.0 * PI * PI * sin_theta),
+        })
+    }
+
+    fn pdf(&self, _origin: &Point3, direction: &Vec3) -> f64 {
+        if self.total_weight <= 0.0 {
+            return 0.0;
+        }
+        let (u, v, sin_theta) = self.to_uv(direction);
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let (i, j) = self.pixel_at(u, v);
+        self.pixel_pdf(i, j) / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn sample_emission(&self, center: &Point3, radius: f64) -> Option<EmissionSample> {
+        let towards = self.sample(center)?;
+        Some(emission_from_infinity(&towards, center, radius))
+    }
+
+    fn pdf_emission(&self, origin: &Point3, _normal: &Vec3, direction: &Vec3) -> f64 {
+        self.pdf(origin, &-*direction)
+    }
+}
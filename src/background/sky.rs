@@ -0,0 +1,235 @@
+use crate::light::{emission_from_infinity, EmissionSample, Light, LightSample};
+use crate::onb::Onb;
+use crate::random;
+use crate::spectrum::xyz_to_rgb;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Angular radius of the sun as seen from the earth, in degrees
+const SUN_RADIUS: f64 = 0.265;
+/// Luminance of the sun outside the atmosphere, in kcd/m² like the sky
+const SUN_LUMINANCE: f64 = 1.6e6;
+
+/// Daylight from the Preetham et al. analytic sky model, with +y up, plus the sun
+/// disk. The lower hemisphere is black. As a light only the sun disk is sampled.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sky {
+    turbidity: f64,
+    sun_direction: Vec3,
+    /// Angle between the sun and the zenith
+    theta_sun: f64,
+    cos_sun_radius: f64,
+    intensity: f64,
+    /// Perez coefficients A to E of the luminance Y and the chromaticities x and y
+    perez: [[f64; 5]; 3],
+    /// Yxy at the zenith
+    zenith: [f64; 3],
+    /// Radiance of a sun disk of the default size, before `intensity`
+    sun_radiance: Color,
+}
+
+/// Perez sky distribution for a direction `theta` from the zenith and `gamma` from the sun
+fn perez(c: &[f64; 5], theta: f64, gamma: f64) -> f64 {
+    (1.0 + c[0] * (c[1] / theta.cos()).exp())
+        * (1.0 + c[2] * (c[3] * gamma).exp() + c[4] * gamma.cos() * gamma.cos())
+}
+
+/// Sun direction at `hour` (local solar time) on `day_of_year` at `latitude` degrees,
+/// as (elevation, azimuth) in degrees with the azimuth measured from north
+pub fn solar_position(latitude: f64, day_of_year: f64, hour: f64) -> (f64, f64) {
+    let latitude = crate::degrees_to_radians(latitude);
+    let declination =
+        -crate::degrees_to_radians(23.44) * (2.0 * PI * (day_of_year + 10.0) / 365.0).cos();
+    let hour_angle = crate::degrees_to_radians(15.0 * (hour - 12.0));
+    let sin_elevation =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let elevation = sin_elevation.clamp(-1.0, 1.0).asin();
+    let cos_azimuth = (declination.sin() - sin_elevation * latitude.sin())
+        / (elevation.cos() * latitude.cos()).max(1e-9);
+    let mut azimuth = cos_azimuth.clamp(-1.0, 1.0).acos().to_degrees();
+    if hour_angle > 0.0 {
+        azimuth = 360.0 - azimuth;
+    }
+    (elevation.to_degrees(), azimuth)
+}
+
+impl Sky {
+    /// `turbidity` ranges from about 2 (very clear) to 10 (hazy). The sun is
+    /// `elevation` degrees above the horizon, clamped to [0, 90], and `azimuth` degrees
+    /// from -z towards +x.
+    pub fn new(turbidity: f64, elevation: f64, azimuth: f64) -> Self {
+        let elevation = crate::degrees_to_radians(elevation.clamp(0.0, 90.0));
+        let azimuth = crate::degrees_to_radians(azimuth);
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+        // Keep the sun slightly above the horizon, where the model breaks down
+        let theta_sun = (PI / 2.0 - elevation).min(PI / 2.0 - 1e-3);
+        let t = turbidity;
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let (t1, t2, t3) = (theta_sun, theta_sun * theta_sun, theta_sun.powi(3));
+        let zenith = [
+            (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192,
+            t * t * (0.00166 * t3 - 0.00375 * t2 + 0.00209 * t1)
+                + t * (-0.02903 * t3 + 0.06377 * t2 - 0.03202 * t1 + 0.00394)
+                + (0.11693 * t3 - 0.21196 * t2 + 0.06052 * t1 + 0.25886),
+            t * t * (0.00275 * t3 - 0.00610 * t2 + 0.00317 * t1)
+                + t * (-0.04214 * t3 + 0.08970 * t2 - 0.04153 * t1 + 0.00516)
+                + (0.15346 * t3 - 0.26756 * t2 + 0.06670 * t1 + 0.26688),
+        ];
+
+        Self {
+            turbidity,
+            sun_direction,
+            theta_sun,
+            cos_sun_radius: crate::degrees_to_radians(SUN_RADIUS).cos(),
+            intensity: 0.1,
+            perez,
+            zenith,
+            sun_radiance: Self::sun_transmittance(turbidity, theta_sun) * SUN_LUMINANCE,
+        }
+    }
+
+    /// The sun at `hour` (local solar time) on `day_of_year` at `latitude` degrees,
+    /// with north along -z
+    pub fn from_location(turbidity: f64, latitude: f64, day_of_year: f64, hour: f64) -> Self {
+        let (elevation, azimuth) = solar_position(latitude, day_of_year, hour);
+        Self::new(turbidity, elevation, azimuth)
+    }
+
+    /// Angular radius of the sun disk in degrees. The sun keeps its power, so larger
+    /// disks give softer shadows without brightening the scene.
+    pub fn with_sun_size(mut self, degrees: f64) -> Self {
+        self.cos_sun_radius = crate::degrees_to_radians(degrees).cos();
+        self
+    }
+
+    /// Scale from luminance in kcd/m² to scene radiance, 0.1 by default
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn sun_direction(&self) -> Vec3 {
+        self.sun_direction
+    }
+
+    pub fn turbidity(&self) -> f64 {
+        self.turbidity
+    }
+
+    /// Rayleigh and aerosol extinction of sunlight along the path through the
+    /// atmosphere, at wavelengths standing for R, G and B
+    fn sun_transmittance(turbidity: f64, theta_sun: f64) -> Color {
+        let air_mass =
+            1.0 / (theta_sun.cos() + 0.15 * (93.885 - theta_sun.to_degrees()).powf(-1.253));
+        let beta = 0.04608 * turbidity - 0.04586;
+        let channel = |lambda: f64| {
+            let rayleigh = (-0.008735 * lambda.powf(-4.08) * air_mass).exp();
+            let aerosol = (-beta * lambda.powf(-1.3) * air_mass).exp();
+            rayleigh * aerosol
+        };
+        Color::new(channel(0.65), channel(0.55), channel(0.45))
+    }
+
+    /// Radiance of the sun disk
+    fn sun_value(&self) -> Color {
+        let default_size = 1.0 - crate::degrees_to_radians(SUN_RADIUS).cos();
+        self.intensity * self.sun_radiance * (default_size / (1.0 - self.cos_sun_radius))
+    }
+
+    fn sky_value(&self, direction: &Vec3) -> Color {
+        let theta = direction.y().clamp(0.0, 1.0).acos().min(PI / 2.0 - 1e-3);
+        let gamma = direction.dot(&self.sun_direction).clamp(-1.0, 1.0).acos();
+        let [y_zenith, x_zenith, yc_zenith] = self.zenith;
+        let luminance = y_zenith * perez(&self.perez[0], theta, gamma)
+            / perez(&self.perez[0], 0.0, self.theta_sun);
+        let x = x_zenith * perez(&self.perez[1], theta, gamma)
+            / perez(&self.perez[1], 0.0, self.theta_sun);
+        let y = yc_zenith * perez(&self.perez[2], theta, gamma)
+            / perez(&self.perez[2], 0.0, self.theta_sun);
+        let xyz = Vec3::new(x / y * luminance, luminance, (1.0 - x - y) / y * luminance);
+        self.intensity * xyz_to_rgb(&xyz)
+    }
+
+    pub fn value(&self, direction: &Vec3) -> Color {
+        let d = direction.unit();
+        if d.y() <= 0.0 {
+            return Color::zero();
+        }
+        let mut color = self.sky_value(&d);
+        if d.dot(&self.sun_direction) >= self.cos_sun_radius {
+            color += self.sun_value();
+        }
+        color
+    }
+}
+
+impl Light for Sky {
+    fn sample(&self, _origin: &Point3) -> Option<LightSample> {
+        // Uniform over the cone of the sun disk
+        let mut rng = random::rng();
+        let cos_theta = 1.0 + rng.gen_range(0.0..1.0) * (self.cos_sun_radius - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+        let direction = Onb::build_from_w(&self.sun_direction).local(&Vec3::new(
+            phi.cos() * sin_theta,
+            phi.sin() * sin_theta,
+            cos_theta,
+        ));
+        if direction.y() <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance: f64::INFINITY,
+            radiance: self.value(&direction),
+            pdf: 1.0 / (2.0 * PI * (1.0 - self.cos_sun_radius)),
+        })
+    }
+
+    fn pdf(&self, _origin: &Point3, direction: &Vec3) -> f64 {
+        let d = direction.unit();
+        if d.y() <= 0.0 || d.dot(&self.sun_direction) < self.cos_sun_radius {
+            return 0.0;
+        }
+        1.0 / (2.0 * PI * (1.0 - self.cos_sun_radius))
+    }
+
+    fn sample_emission(&self, center: &Point3, radius: f64) -> Option<EmissionSample> {
+        let towards = self.sample(center)?;
+        Some(emission_from_infinity(&towards, center, radius))
+    }
+
+    fn pdf_emission(&self, origin: &Point3, _normal: &Vec3, direction: &Vec3) -> f64 {
+        self.pdf(origin, &-*direction)
+    }
+}
@@ -1,8 +1,10 @@
 use crate::degrees_to_radians;
+use crate::random;
 use crate::ray::Ray;
 use crate::vec3;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct Camera {
@@ -10,11 +12,31 @@ pub struct Camera {
     lower_left_corner: vec3::Point3,
     horizontal: vec3::Vec3,
     vertical: vec3::Vec3,
+    u: vec3::Vec3,
+    v: vec3::Vec3,
+    /// Points backwards, away from the scene
+    w: vec3::Vec3,
+    focus_dist: f64,
     lens_radius: f64,
     time0: f64,
     time1: f64,
 }
 
+/// A point on the lens seen from elsewhere in the scene
+pub struct LensSample {
+    pub point: vec3::Point3,
+    /// Unit vector from the point seen towards the lens
+    pub direction: vec3::Vec3,
+    pub distance: f64,
+    /// Importance `Camera::importance` gives to the ray back towards the point
+    pub importance: f64,
+    /// Solid angle density of `direction`, seen from the other point
+    pub pdf: f64,
+    /// Film position the point appears at, in the coordinates of `Camera::get_ray`
+    pub s: f64,
+    pub t: f64,
+}
+
 impl Camera {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -47,20 +69,105 @@ impl Camera {
             lower_left_corner,
             horizontal,
             vertical,
+            u,
+            v,
+            w,
+            focus_dist,
             lens_radius: aperture / 2.0,
             time0: t0,
             time1: t1,
         }
     }
 
-    pub fn get_ray(&self, u: f64, v: f64) -> Ray {
+    /// Ray through the film at (`s`, `t`), both from 0 to 1, from the bottom left corner
+    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
         let rd = self.lens_radius * vec3::Vec3::rand_in_unit_disk();
-        let offset = vec3::Vec3::new(u * rd.x(), v * rd.y(), 0.0);
+        let offset = self.u * rd.x() + self.v * rd.y();
         Ray::new(
             &(self.origin + offset),
-            &(self.lower_left_corner + u * self.horizontal + v * self.vertical
+            &(self.lower_left_corner + s * self.horizontal + t * self.vertical
                 - (self.origin + offset)),
-            rand::thread_rng().gen_range(self.time0..self.time1),
+            random::rng().gen_range(self.time0..self.time1),
         )
     }
+
+    /// Area of the film at unit distance from the lens
+    fn film_area(&self) -> f64 {
+        self.horizontal.len() * self.vertical.len() / (self.focus_dist * self.focus_dist)
+    }
+
+    fn lens_area(&self) -> f64 {
+        if self.lens_radius > 0.0 {
+            PI * self.lens_radius * self.lens_radius
+        } else {
+            1.0
+        }
+    }
+
+    /// Film position, in the coordinates of `get_ray`, of the ray leaving the lens at
+    /// `origin` along `direction`, if it passes through the film
+    pub fn film_position(
+        &self,
+        origin: &vec3::Point3,
+        direction: &vec3::Vec3,
+    ) -> Option<(f64, f64)> {
+        let d = direction.unit();
+        let cos_theta = -d.dot(&self.w);
+        if cos_theta <= 0.0 {
+            return None;
+        }
+        let focus = *origin + d * (self.focus_dist / cos_theta) - self.lower_left_corner;
+        let s = focus.dot(&self.horizontal) / self.horizontal.power();
+        let t = focus.dot(&self.vertical) / self.vertical.power();
+        if !(0.0..1.0).contains(&s) || !(0.0..1.0).contains(&t) {
+            return None;
+        }
+        Some((s, t))
+    }
+
+    /// Importance of the ray leaving the lens at `origin` along `direction`, normalized
+    /// over the whole film, and where it passes through the film
+    pub fn importance(
+        &self,
+        origin: &vec3::Point3,
+        direction: &vec3::Vec3,
+    ) -> Option<(f64, f64, f64)> {
+        let (s, t) = self.film_position(origin, direction)?;
+        let cos_theta = -direction.unit().dot(&self.w);
+        let cos2_theta = cos_theta * cos_theta;
+        let importance = 1.0 / (self.film_area() * self.lens_area() * cos2_theta * cos2_theta);
+        Some((importance, s, t))
+    }
+
+    /// Solid angle density with which `get_ray` picks `direction`, for a given lens point
+    pub fn pdf_direction(&self, direction: &vec3::Vec3) -> f64 {
+        let cos_theta = -direction.unit().dot(&self.w);
+        if cos_theta <= 0.0 {
+            return 0.0;
+        }
+        1.0 / (self.film_area() * cos_theta * cos_theta * cos_theta)
+    }
+
+    /// Samples a point on the lens that sees `p`
+    pub fn sample_lens(&self, p: &vec3::Point3) -> Option<LensSample> {
+        let rd = self.lens_radius * vec3::Vec3::rand_in_unit_disk();
+        let point = self.origin + self.u * rd.x() + self.v * rd.y();
+        let to_lens = point - *p;
+        let distance = to_lens.len();
+        if distance == 0.0 {
+            return None;
+        }
+        let direction = to_lens / distance;
+        let (importance, s, t) = self.importance(&point, &-direction)?;
+        let cos_theta = direction.dot(&self.w);
+        Some(LensSample {
+            point,
+            direction,
+            distance,
+            importance,
+            pdf: distance * distance / (cos_theta * self.lens_area()),
+            s,
+            t,
+        })
+    }
 }
@@ -0,0 +1,66 @@
+use crate::vec3::Color;
+
+/// Light arriving at the film at (`s`, `t`), in the coordinates of `Camera::get_ray`,
+/// found by an integrator while tracing a sample of another pixel
+#[derive(Debug, Clone, Copy)]
+pub struct Splat {
+    pub s: f64,
+    pub t: f64,
+    pub color: Color,
+}
+
+/// Sums of the samples of each pixel, and of the splats landing on it. Rows are counted
+/// from the bottom, like `t`.
+#[derive(Debug, Clone)]
+pub struct Film {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    splats: Vec<Color>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::zero(); width * height],
+            splats: vec![Color::zero(); width * height],
+        }
+    }
+
+    pub fn add_sample(&mut self, i: usize, j: usize, color: &Color) {
+        self.pixels[j * self.width + i] += *color;
+    }
+
+    pub fn add_splat(&mut self, splat: &Splat) {
+        let i = ((splat.s * self.width as f64) as usize).min(self.width - 1);
+        let j = ((splat.t * self.height as f64) as usize).min(self.height - 1);
+        self.splats[j * self.width + i] += splat.color;
+    }
+
+    /// Adds the samples and splats of `other`, of the same size
+    pub fn merge(&mut self, other: &Film) {
+        for (pixel, other) in self.pixels.iter_mut().zip(&other.pixels) {
+            *pixel += *other;
+        }
+        for (splat, other) in self.splats.iter_mut().zip(&other.splats) {
+            *splat += *other;
+        }
+    }
+
+    /// Gamma corrected 8-bit RGB, from the top row down. Splats are estimates of the
+    /// whole pixel for every sample taken, like the samples themselves.
+    pub fn to_rgb(&self, samples_per_pixel: usize) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.width * self.height * 3);
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                let index = j * self.width + i;
+                let (r, g, b) =
+                    (self.pixels[index] + self.splats[index]).get_color(samples_per_pixel as i64);
+                buffer.extend_from_slice(&[r, g, b]);
+            }
+        }
+        buffer
+    }
+}
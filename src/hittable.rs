@@ -24,6 +24,9 @@ pub use xz_rect::XZRect;
 pub mod yz_rect;
 pub use yz_rect::YZRect;
 
+pub mod triangle;
+pub use triangle::Triangle;
+
 pub mod cuboid;
 pub use cuboid::Cuboid;
 
@@ -38,7 +41,10 @@ pub mod rotate_z;
 pub use rotate_z::RotateZ;
 
 pub mod constant_medium;
-pub use constant_medium::ConstantMedium;
+pub use constant_medium::{ConstantMedium, MediumSegment};
+
+pub mod medium_emission;
+pub use medium_emission::{blackbody_emission, Grid, MediumEmission};
 
 use serde::{Deserialize, Serialize};
 use std::boxed::Box;
@@ -54,10 +60,17 @@ fn get_sphere_uv(p: &vec3::Point3) -> (f64, f64) {
     (u, v)
 }
 
+/// Direction in which the u of `get_sphere_uv` grows at p on a unit sphere
+fn get_sphere_dpdu(p: &vec3::Point3) -> vec3::Vec3 {
+    vec3::Vec3::new(p.z(), 0.0, -p.x())
+}
+
 #[derive(Clone)]
 pub struct HitRecord {
     pub p: vec3::Point3,
     pub normal: vec3::Vec3,
+    /// Direction in which `u` grows along the surface, zero where it has none
+    pub dpdu: vec3::Vec3,
     pub material: Option<material::MaterialEnum>,
     pub t: f64,
     pub u: f64,
@@ -76,6 +89,7 @@ impl HitRecord {
         HitRecord {
             p: vec3::Point3::zero(),
             normal: vec3::Vec3::zero(),
+            dpdu: vec3::Vec3::zero(),
             material: None,
             t: 0.0,
             u: 0.0,
@@ -88,6 +102,48 @@ impl HitRecord {
 pub trait Hittable {
     fn hit(&self, r: &ray::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
     fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool;
+    /// Solid angle density with which `random` picks `direction` from `origin`
+    fn pdf_value(&self, _origin: &vec3::Point3, _direction: &vec3::Vec3) -> f64 {
+        0.0
+    }
+    /// Direction from `origin` towards a random point on the surface
+    fn random(&self, _origin: &vec3::Point3) -> vec3::Vec3 {
+        vec3::Vec3::new(1.0, 0.0, 0.0)
+    }
+    /// Appends the emissive primitives, in world space, that can be sampled with `random`
+    fn lights(&self, _lights: &mut Vec<HittableEnum>) {}
+    /// Rough estimate of the power an emissive primitive gives off, to pick among lights
+    fn power(&self) -> f64 {
+        0.0
+    }
+    /// Surface area of a primitive that `sample_surface` supports
+    fn area(&self) -> f64 {
+        0.0
+    }
+    /// Uniformly distributed point on the surface, with the outward normal there
+    fn sample_surface(&self) -> (vec3::Point3, vec3::Vec3) {
+        (vec3::Point3::zero(), vec3::Vec3::zero())
+    }
+    /// Appends the media `r` goes through between `t_min` and `t_max`
+    fn media(&self, _r: &ray::Ray, _t_min: f64, _t_max: f64, _segments: &mut Vec<MediumSegment>) {}
+    /// Fraction of the light along `r` between `t_min` and `t_max` that gets through: none
+    /// if a surface is in the way, what the media let through otherwise
+    fn transmittance(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> f64 {
+        if self.hit(r, t_min, t_max, &mut HitRecord::new()) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+    /// Whether it holds media that give off light, see `emission`
+    fn glows(&self) -> bool {
+        false
+    }
+    /// Light that emissive media give off along `r` between `t_min` and `t_max`, dimmed
+    /// by the absorption of the volume `r` is in but not by the media themselves
+    fn emission(&self, _r: &ray::Ray, _t_min: f64, _t_max: f64) -> vec3::Color {
+        vec3::Color::zero()
+    }
     fn set_front_face(&self, r: &ray::Ray, outward_normal: &vec3::Vec3, record: &mut HitRecord) {
         let is_front_face = r.direction.dot(outward_normal) <= 0.0;
         record.front_face = is_front_face;
@@ -108,6 +164,7 @@ pub enum HittableEnum {
     XYRect(XYRect),
     XZRect(XZRect),
     YZRect(YZRect),
+    Triangle(Triangle),
     Cuboid(Cuboid),
     Translation(Translation),
     RotateX(Box<RotateX>),
@@ -126,6 +183,7 @@ impl Hittable for HittableEnum {
             HittableEnum::XYRect(rect) => rect.hit(r, t_min, t_max, rec),
             HittableEnum::XZRect(rect) => rect.hit(r, t_min, t_max, rec),
             HittableEnum::YZRect(rect) => rect.hit(r, t_min, t_max, rec),
+            HittableEnum::Triangle(t) => t.hit(r, t_min, t_max, rec),
             HittableEnum::Cuboid(c) => c.hit(r, t_min, t_max, rec),
             HittableEnum::Translation(t) => t.hit(r, t_min, t_max, rec),
             HittableEnum::RotateX(rotate) => rotate.hit(r, t_min, t_max, rec),
@@ -135,6 +193,82 @@ impl Hittable for HittableEnum {
         }
     }
 
+    fn media(&self, r: &ray::Ray, t_min: f64, t_max: f64, segments: &mut Vec<MediumSegment>) {
+        match self {
+            HittableEnum::HittableList(h) => h.media(r, t_min, t_max, segments),
+            HittableEnum::BvhNode(b) => b.media(r, t_min, t_max, segments),
+            HittableEnum::Sphere(s) => s.media(r, t_min, t_max, segments),
+            HittableEnum::MovingSphere(s) => s.media(r, t_min, t_max, segments),
+            HittableEnum::XYRect(rect) => rect.media(r, t_min, t_max, segments),
+            HittableEnum::XZRect(rect) => rect.media(r, t_min, t_max, segments),
+            HittableEnum::YZRect(rect) => rect.media(r, t_min, t_max, segments),
+            HittableEnum::Triangle(t) => t.media(r, t_min, t_max, segments),
+            HittableEnum::Cuboid(c) => c.media(r, t_min, t_max, segments),
+            HittableEnum::Translation(t) => t.media(r, t_min, t_max, segments),
+            HittableEnum::RotateX(rotate) => rotate.media(r, t_min, t_max, segments),
+            HittableEnum::RotateY(rotate) => rotate.media(r, t_min, t_max, segments),
+            HittableEnum::RotateZ(rotate) => rotate.media(r, t_min, t_max, segments),
+            HittableEnum::ConstantMedium(c) => c.media(r, t_min, t_max, segments),
+        }
+    }
+
+    fn transmittance(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> f64 {
+        match self {
+            HittableEnum::HittableList(h) => h.transmittance(r, t_min, t_max),
+            HittableEnum::BvhNode(b) => b.transmittance(r, t_min, t_max),
+            HittableEnum::Sphere(s) => s.transmittance(r, t_min, t_max),
+            HittableEnum::MovingSphere(s) => s.transmittance(r, t_min, t_max),
+            HittableEnum::XYRect(rect) => rect.transmittance(r, t_min, t_max),
+            HittableEnum::XZRect(rect) => rect.transmittance(r, t_min, t_max),
+            HittableEnum::YZRect(rect) => rect.transmittance(r, t_min, t_max),
+            HittableEnum::Triangle(t) => t.transmittance(r, t_min, t_max),
+            HittableEnum::Cuboid(c) => c.transmittance(r, t_min, t_max),
+            HittableEnum::Translation(t) => t.transmittance(r, t_min, t_max),
+            HittableEnum::RotateX(rotate) => rotate.transmittance(r, t_min, t_max),
+            HittableEnum::RotateY(rotate) => rotate.transmittance(r, t_min, t_max),
+            HittableEnum::RotateZ(rotate) => rotate.transmittance(r, t_min, t_max),
+            HittableEnum::ConstantMedium(c) => c.transmittance(r, t_min, t_max),
+        }
+    }
+
+    fn glows(&self) -> bool {
+        match self {
+            HittableEnum::HittableList(h) => h.glows(),
+            HittableEnum::BvhNode(b) => b.glows(),
+            HittableEnum::Sphere(s) => s.glows(),
+            HittableEnum::MovingSphere(s) => s.glows(),
+            HittableEnum::XYRect(rect) => rect.glows(),
+            HittableEnum::XZRect(rect) => rect.glows(),
+            HittableEnum::YZRect(rect) => rect.glows(),
+            HittableEnum::Triangle(t) => t.glows(),
+            HittableEnum::Cuboid(c) => c.glows(),
+            HittableEnum::Translation(t) => t.glows(),
+            HittableEnum::RotateX(rotate) => rotate.glows(),
+            HittableEnum::RotateY(rotate) => rotate.glows(),
+            HittableEnum::RotateZ(rotate) => rotate.glows(),
+            HittableEnum::ConstantMedium(c) => c.glows(),
+        }
+    }
+
+    fn emission(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> vec3::Color {
+        match self {
+            HittableEnum::HittableList(h) => h.emission(r, t_min, t_max),
+            HittableEnum::BvhNode(b) => b.emission(r, t_min, t_max),
+            HittableEnum::Sphere(s) => s.emission(r, t_min, t_max),
+            HittableEnum::MovingSphere(s) => s.emission(r, t_min, t_max),
+            HittableEnum::XYRect(rect) => rect.emission(r, t_min, t_max),
+            HittableEnum::XZRect(rect) => rect.emission(r, t_min, t_max),
+            HittableEnum::YZRect(rect) => rect.emission(r, t_min, t_max),
+            HittableEnum::Triangle(t) => t.emission(r, t_min, t_max),
+            HittableEnum::Cuboid(c) => c.emission(r, t_min, t_max),
+            HittableEnum::Translation(t) => t.emission(r, t_min, t_max),
+            HittableEnum::RotateX(rotate) => rotate.emission(r, t_min, t_max),
+            HittableEnum::RotateY(rotate) => rotate.emission(r, t_min, t_max),
+            HittableEnum::RotateZ(rotate) => rotate.emission(r, t_min, t_max),
+            HittableEnum::ConstantMedium(c) => c.emission(r, t_min, t_max),
+        }
+    }
+
     fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool {
         match self {
             HittableEnum::HittableList(h) => h.bounding_box(time0, time1, output_box),
@@ -144,6 +278,7 @@ impl Hittable for HittableEnum {
             HittableEnum::XYRect(r) => r.bounding_box(time0, time1, output_box),
             HittableEnum::XZRect(r) => r.bounding_box(time0, time1, output_box),
             HittableEnum::YZRect(r) => r.bounding_box(time0, time1, output_box),
+            HittableEnum::Triangle(t) => t.bounding_box(time0, time1, output_box),
             HittableEnum::Cuboid(c) => c.bounding_box(time0, time1, output_box),
             HittableEnum::Translation(t) => t.bounding_box(time0, time1, output_box),
             HittableEnum::RotateX(r) => r.bounding_box(time0, time1, output_box),
@@ -152,9 +287,103 @@ impl Hittable for HittableEnum {
             HittableEnum::ConstantMedium(c) => c.bounding_box(time0, time1, output_box),
         }
     }
+
+    fn pdf_value(&self, origin: &vec3::Point3, direction: &vec3::Vec3) -> f64 {
+        match self {
+            HittableEnum::Sphere(s) => s.pdf_value(origin, direction),
+            HittableEnum::XYRect(r) => r.pdf_value(origin, direction),
+            HittableEnum::XZRect(r) => r.pdf_value(origin, direction),
+            HittableEnum::YZRect(r) => r.pdf_value(origin, direction),
+            HittableEnum::Triangle(t) => t.pdf_value(origin, direction),
+            HittableEnum::Translation(t) => t.pdf_value(origin, direction),
+            HittableEnum::RotateX(r) => r.pdf_value(origin, direction),
+            HittableEnum::RotateY(r) => r.pdf_value(origin, direction),
+            HittableEnum::RotateZ(r) => r.pdf_value(origin, direction),
+            _ => 0.0,
+        }
+    }
+
+    fn random(&self, origin: &vec3::Point3) -> vec3::Vec3 {
+        match self {
+            HittableEnum::Sphere(s) => s.random(origin),
+            HittableEnum::XYRect(r) => r.random(origin),
+            HittableEnum::XZRect(r) => r.random(origin),
+            HittableEnum::YZRect(r) => r.random(origin),
+            HittableEnum::Triangle(t) => t.random(origin),
+            HittableEnum::Translation(t) => t.random(origin),
+            HittableEnum::RotateX(r) => r.random(origin),
+            HittableEnum::RotateY(r) => r.random(origin),
+            HittableEnum::RotateZ(r) => r.random(origin),
+            _ => vec3::Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        match self {
+            HittableEnum::HittableList(h) => h.lights(lights),
+            HittableEnum::BvhNode(b) => b.lights(lights),
+            HittableEnum::Sphere(s) => s.lights(lights),
+            HittableEnum::XYRect(r) => r.lights(lights),
+            HittableEnum::XZRect(r) => r.lights(lights),
+            HittableEnum::YZRect(r) => r.lights(lights),
+            HittableEnum::Triangle(t) => t.lights(lights),
+            HittableEnum::Cuboid(c) => c.lights(lights),
+            HittableEnum::Translation(t) => t.lights(lights),
+            HittableEnum::RotateX(r) => r.lights(lights),
+            HittableEnum::RotateY(r) => r.lights(lights),
+            HittableEnum::RotateZ(r) => r.lights(lights),
+            // Moving spheres and media can't be sampled directly
+            HittableEnum::MovingSphere(_) | HittableEnum::ConstantMedium(_) => {}
+        }
+    }
+
+    fn power(&self) -> f64 {
+        match self {
+            HittableEnum::Sphere(s) => s.power(),
+            HittableEnum::XYRect(r) => r.power(),
+            HittableEnum::XZRect(r) => r.power(),
+            HittableEnum::YZRect(r) => r.power(),
+            HittableEnum::Triangle(t) => t.power(),
+            HittableEnum::Translation(t) => t.power(),
+            HittableEnum::RotateX(r) => r.power(),
+            HittableEnum::RotateY(r) => r.power(),
+            HittableEnum::RotateZ(r) => r.power(),
+            _ => 0.0,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        match self {
+            HittableEnum::Sphere(s) => s.area(),
+            HittableEnum::XYRect(r) => r.area(),
+            HittableEnum::XZRect(r) => r.area(),
+            HittableEnum::YZRect(r) => r.area(),
+            HittableEnum::Triangle(t) => t.area(),
+            HittableEnum::Translation(t) => t.area(),
+            HittableEnum::RotateX(r) => r.area(),
+            HittableEnum::RotateY(r) => r.area(),
+            HittableEnum::RotateZ(r) => r.area(),
+            _ => 0.0,
+        }
+    }
+
+    fn sample_surface(&self) -> (vec3::Point3, vec3::Vec3) {
+        match self {
+            HittableEnum::Sphere(s) => s.sample_surface(),
+            HittableEnum::XYRect(r) => r.sample_surface(),
+            HittableEnum::XZRect(r) => r.sample_surface(),
+            HittableEnum::YZRect(r) => r.sample_surface(),
+            HittableEnum::Triangle(t) => t.sample_surface(),
+            HittableEnum::Translation(t) => t.sample_surface(),
+            HittableEnum::RotateX(r) => r.sample_surface(),
+            HittableEnum::RotateY(r) => r.sample_surface(),
+            HittableEnum::RotateZ(r) => r.sample_surface(),
+            _ => (vec3::Point3::zero(), vec3::Vec3::zero()),
+        }
+    }
 }
 
-fn surrounding_box(box0: &AABB, box1: &AABB) -> AABB {
+pub fn surrounding_box(box0: &AABB, box1: &AABB) -> AABB {
     let small = vec3::Point3::new(
         box0.min.x().min(box1.min.x()),
         box0.min.y().min(box1.min.y()),
@@ -15,6 +15,14 @@ impl AABB {
         }
     }
 
+    pub fn min(&self) -> vec3::Point3 {
+        self.min
+    }
+
+    pub fn max(&self) -> vec3::Point3 {
+        self.max
+    }
+
     pub fn hit(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> bool {
         for a in 0..3 {
             let inv_d = 1.0 / r.direction[a];
@@ -1,13 +1,31 @@
-use super::{surrounding_box, HitRecord, Hittable, HittableEnum, AABB};
+use super::{surrounding_box, HitRecord, Hittable, HittableEnum, MediumSegment, AABB};
 use crate::{ray, vec3};
 use serde::{Deserialize, Serialize};
 use std::boxed::Box;
+use std::cell::Cell;
+
+thread_local! {
+    static NODES_VISITED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Number of BVH nodes `hit` visited on this thread since `reset_nodes_visited`
+pub fn nodes_visited() -> usize {
+    NODES_VISITED.with(|n| n.get())
+}
+
+pub fn reset_nodes_visited() {
+    NODES_VISITED.with(|n| n.set(0));
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BvhNode {
     left: HittableEnum,
     right: HittableEnum,
     bbox: AABB,
+    /// `left` and `right` are the same single object
+    leaf: bool,
+    /// Whether either child glows, so that `emission` can skip the rest
+    glows: bool,
 }
 
 fn box_compare(a: &HittableEnum, b: &HittableEnum, axis: usize) -> std::cmp::Ordering {
@@ -87,13 +105,21 @@ impl BvhNode {
             eprintln!("No bounding box in bvh_node constructor.");
         }
         let bbox = surrounding_box(&box_left, &box_right);
+        let glows = left.glows() || right.glows();
 
-        Self { left, right, bbox }
+        Self {
+            left,
+            right,
+            bbox,
+            leaf: object_span == 1,
+            glows,
+        }
     }
 }
 
 impl Hittable for BvhNode {
     fn hit(&self, r: &ray::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        NODES_VISITED.with(|n| n.set(n.get() + 1));
         if !self.bbox.hit(r, t_min, t_max) {
             return false;
         }
@@ -115,4 +141,47 @@ impl Hittable for BvhNode {
             panic!("No bounding box in bvh_node::bounding_box.");
         }
     }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        self.left.lights(lights);
+        if !self.leaf {
+            self.right.lights(lights);
+        }
+    }
+
+    fn media(&self, r: &ray::Ray, t_min: f64, t_max: f64, segments: &mut Vec<MediumSegment>) {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return;
+        }
+        self.left.media(r, t_min, t_max, segments);
+        if !self.leaf {
+            self.right.media(r, t_min, t_max, segments);
+        }
+    }
+
+    fn transmittance(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> f64 {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return 1.0;
+        }
+        let left = self.left.transmittance(r, t_min, t_max);
+        if self.leaf || left == 0.0 {
+            return left;
+        }
+        left * self.right.transmittance(r, t_min, t_max)
+    }
+
+    fn glows(&self) -> bool {
+        self.glows
+    }
+
+    fn emission(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> vec3::Color {
+        if !self.glows || !self.bbox.hit(r, t_min, t_max) {
+            return vec3::Color::zero();
+        }
+        let left = self.left.emission(r, t_min, t_max);
+        if self.leaf {
+            return left;
+        }
+        left + self.right.emission(r, t_min, t_max)
+    }
 }
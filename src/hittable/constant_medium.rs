@@ -1,16 +1,28 @@
-use super::{HitRecord, Hittable, HittableEnum, AABB};
+use super::{HitRecord, Hittable, HittableEnum, MediumEmission, AABB};
 use crate::material::{Isotropic, MaterialEnum};
+use crate::random;
 use crate::ray::Ray;
 use crate::texture::TextureEnum;
-use crate::vec3::Vec3;
+use crate::vec3::{Color, Vec3};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+/// Stretch of a ray inside a medium of constant density
+#[derive(Debug, Clone)]
+pub struct MediumSegment {
+    /// Where the ray enters and leaves, in its parameter
+    pub t0: f64,
+    pub t1: f64,
+    pub density: f64,
+    pub phase_function: MaterialEnum,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ConstantMedium {
     boundary: HittableEnum,
     phase_function: MaterialEnum,
     neg_inv_density: f64,
+    emission: Option<MediumEmission>,
 }
 
 impl ConstantMedium {
@@ -19,7 +31,36 @@ impl ConstantMedium {
             boundary,
             phase_function: MaterialEnum::Isotropic(Isotropic::new(&tex)),
             neg_inv_density: -1.0 / density,
+            emission: None,
+        }
+    }
+
+    /// Makes the medium glow, e.g. for fire. Integrators collect the light along rays
+    /// through it; it isn't sampled as a light.
+    pub fn with_emission(mut self, emission: MediumEmission) -> Self {
+        self.emission = Some(emission);
+        self
+    }
+
+    /// Where `r` is inside the boundary between `t_min` and `t_max`
+    fn inside(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut rec1 = HitRecord::default();
+        let mut rec2 = HitRecord::default();
+        if !self
+            .boundary
+            .hit(r, f64::NEG_INFINITY, f64::INFINITY, &mut rec1)
+            || !self
+                .boundary
+                .hit(r, rec1.t + 0.0001, f64::INFINITY, &mut rec2)
+        {
+            return None;
         }
+        let t0 = rec1.t.max(t_min).max(0.0);
+        let t1 = rec2.t.min(t_max);
+        if t0 >= t1 {
+            return None;
+        }
+        Some((t0, t1))
     }
 }
 
@@ -62,8 +103,7 @@ impl Hittable for ConstantMedium {
 
         let ray_length = r.direction.len();
         let distance_inside_boundary = (rec2.t - rec1.t) * ray_length;
-        let hit_distance =
-            self.neg_inv_density * (rand::thread_rng().gen_range(0.0..1.0) as f64).ln();
+        let hit_distance = self.neg_inv_density * random::rng().gen_range(0.0..1.0_f64).ln();
 
         if hit_distance > distance_inside_boundary {
             return false;
@@ -79,6 +119,7 @@ impl Hittable for ConstantMedium {
         }
 
         rec.normal = Vec3::new(1.0, 0.0, 0.0); // this is arbitrary
+        rec.dpdu = Vec3::zero();
         rec.front_face = true;
         rec.material = Some(self.phase_function.clone());
 
@@ -88,4 +129,49 @@ impl Hittable for ConstantMedium {
     fn bounding_box(&self, time0: f64, time1: f64, output_box: &mut AABB) -> bool {
         self.boundary.bounding_box(time0, time1, output_box)
     }
+
+    fn media(&self, r: &Ray, t_min: f64, t_max: f64, segments: &mut Vec<MediumSegment>) {
+        if let Some((t0, t1)) = self.inside(r, t_min, t_max) {
+            segments.push(MediumSegment {
+                t0,
+                t1,
+                density: -1.0 / self.neg_inv_density,
+                phase_function: self.phase_function.clone(),
+            });
+        }
+    }
+
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        match self.inside(r, t_min, t_max) {
+            Some((t0, t1)) => ((t1 - t0) * r.direction.len() / self.neg_inv_density).exp(),
+            None => 1.0,
+        }
+    }
+
+    fn glows(&self) -> bool {
+        self.emission.is_some()
+    }
+
+    fn emission(&self, r: &Ray, t_min: f64, t_max: f64) -> Color {
+        let emission = match &self.emission {
+            Some(emission) => emission,
+            None => return Color::zero(),
+        };
+        let (t0, t1) = match self.inside(r, t_min, t_max) {
+            Some(inside) => inside,
+            None => return Color::zero(),
+        };
+
+        // Stratified points along the ray
+        let length = r.direction.len();
+        let steps = emission.steps((t1 - t0) * length);
+        let dt = (t1 - t0) / steps as f64;
+        let mut rng = random::rng();
+        let mut radiance = Color::zero();
+        for i in 0..steps {
+            let t = t0 + (i as f64 + rng.gen_range(0.0..1.0)) * dt;
+            radiance += r.interior.transmittance(t * length) * emission.value(&r.at(t));
+        }
+        radiance * (dt * length)
+    }
 }
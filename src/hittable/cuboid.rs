@@ -1,6 +1,6 @@
 use super::{HitRecord, Hittable, HittableEnum, HittableList, XYRect, XZRect, YZRect, AABB};
 use crate::material::MaterialEnum;
-use crate::vec3::Point3;
+use crate::vec3::{Point3, Vec3};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -72,11 +72,27 @@ impl Cuboid {
 
 impl Hittable for Cuboid {
     fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        self.sides.hit(r, t_min, t_max, rec)
+        if !self.sides.hit(r, t_min, t_max, rec) {
+            return false;
+        }
+        // The sides face along +x, +y or +z, the box outwards: towards its far side
+        let center = (self.box_min + self.box_max) * 0.5;
+        let mut outward_normal = Vec3::zero();
+        for axis in 0..3 {
+            if rec.normal[axis] != 0.0 {
+                outward_normal[axis] = (rec.p[axis] - center[axis]).signum();
+            }
+        }
+        self.set_front_face(r, &outward_normal, rec);
+        true
     }
 
     fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut AABB) -> bool {
         *output_box = AABB::new(&self.box_min, &self.box_max);
         true
     }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        self.sides.lights(lights);
+    }
 }
@@ -1,4 +1,4 @@
-use super::{surrounding_box, HitRecord, Hittable, HittableEnum, AABB};
+use super::{surrounding_box, HitRecord, Hittable, HittableEnum, MediumSegment, AABB};
 use crate::{ray, vec3};
 use serde::{Deserialize, Serialize};
 
@@ -70,4 +70,39 @@ impl Hittable for HittableList {
         }
         true
     }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        for object in &self.objects {
+            object.lights(lights);
+        }
+    }
+
+    fn media(&self, r: &ray::Ray, t_min: f64, t_max: f64, segments: &mut Vec<MediumSegment>) {
+        for object in &self.objects {
+            object.media(r, t_min, t_max, segments);
+        }
+    }
+
+    fn transmittance(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> f64 {
+        let mut transmittance = 1.0;
+        for object in &self.objects {
+            transmittance *= object.transmittance(r, t_min, t_max);
+            if transmittance == 0.0 {
+                break;
+            }
+        }
+        transmittance
+    }
+
+    fn glows(&self) -> bool {
+        self.objects.iter().any(|object| object.glows())
+    }
+
+    fn emission(&self, r: &ray::Ray, t_min: f64, t_max: f64) -> vec3::Color {
+        let mut emission = vec3::Color::zero();
+        for object in &self.objects {
+            emission += object.emission(r, t_min, t_max);
+        }
+        emission
+    }
 }
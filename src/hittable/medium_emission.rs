@@ -0,0 +1,180 @@
+use crate::spectrum::blackbody::planck;
+use crate::spectrum::{luminance, spectrum_function_to_rgb};
+use crate::texture::TextureEnum;
+use crate::vec3::{Color, Point3};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Temperature (K) at which `blackbody_emission` has a luminance of 1
+pub const REFERENCE_TEMPERATURE: f64 = 1500.0;
+/// Temperatures are looked up in steps of `TEMPERATURE_STEP` up to `MAX_TEMPERATURE`
+const MAX_TEMPERATURE: f64 = 12000.0;
+const TEMPERATURE_STEP: f64 = 25.0;
+
+/// Color of a blackbody at `temperature` (K), as bright relative to one at
+/// `REFERENCE_TEMPERATURE` as Planck's law makes it, so that hotter gas glows brighter
+/// as well as whiter
+pub fn blackbody_emission(temperature: f64) -> Color {
+    static TABLE: OnceLock<Vec<Color>> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let reference = luminance(&spectrum_function_to_rgb(|lambda| {
+            planck(lambda, REFERENCE_TEMPERATURE)
+        }));
+        (0..=(MAX_TEMPERATURE / TEMPERATURE_STEP) as usize)
+            .map(|i| {
+                let temperature = i as f64 * TEMPERATURE_STEP;
+                let c = spectrum_function_to_rgb(|lambda| planck(lambda, temperature));
+                // Deep red is out of gamut
+                Color::new(c.x().max(0.0), c.y().max(0.0), c.z().max(0.0)) / reference
+            })
+            .collect()
+    });
+
+    let x = (temperature.max(0.0) / TEMPERATURE_STEP).min((table.len() - 1) as f64);
+    let i = (x as usize).min(table.len() - 2);
+    let f = x - i as f64;
+    table[i] * (1.0 - f) + table[i + 1] * f
+}
+
+/// Values on a lattice of `nx` by `ny` by `nz` points spanning a box, e.g. from a fluid
+/// simulation, interpolated trilinearly in between and 0 outside
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Grid {
+    min: Point3,
+    max: Point3,
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    values: Vec<f64>,
+}
+
+impl Grid {
+    /// `values` run along x first, then y, then z
+    pub fn new(
+        min: Point3,
+        max: Point3,
+        nx: usize,
+        ny: usize,
+        nz: usize,
+        values: Vec<f64>,
+    ) -> Self {
+        assert!(
+            nx >= 2 && ny >= 2 && nz >= 2,
+            "Grid needs 2 points along each axis"
+        );
+        assert_eq!(values.len(), nx * ny * nz, "Grid needs nx * ny * nz values");
+        Self {
+            min,
+            max,
+            nx,
+            ny,
+            nz,
+            values,
+        }
+    }
+
+    /// Grid with the value of `f` at each lattice point
+    pub fn from_fn(
+        min: Point3,
+        max: Point3,
+        nx: usize,
+        ny: usize,
+        nz: usize,
+        f: impl Fn(&Point3) -> f64,
+    ) -> Self {
+        let extent = max - min;
+        let mut values = Vec::with_capacity(nx * ny * nz);
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    values.push(f(&Point3::new(
+                        min.x() + extent.x() * i as f64 / (nx - 1) as f64,
+                        min.y() + extent.y() * j as f64 / (ny - 1) as f64,
+                        min.z() + extent.z() * k as f64 / (nz - 1) as f64,
+                    )));
+                }
+            }
+        }
+        Self::new(min, max, nx, ny, nz, values)
+    }
+
+    pub fn value(&self, p: &Point3) -> f64 {
+        let n = [self.nx, self.ny, self.nz];
+        let mut index = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            let x = (p[axis] - self.min[axis]) / (self.max[axis] - self.min[axis])
+                * (n[axis] - 1) as f64;
+            if !(0.0..=(n[axis] - 1) as f64).contains(&x) {
+                return 0.0;
+            }
+            index[axis] = (x as usize).min(n[axis] - 2);
+            fraction[axis] = x - index[axis] as f64;
+        }
+
+        let mut value = 0.0;
+        for corner in 0..8 {
+            let mut weight = 1.0;
+            let mut offset = 0;
+            let mut stride = 1;
+            for axis in 0..3 {
+                let side = (corner >> axis) & 1;
+                weight *= if side == 1 {
+                    fraction[axis]
+                } else {
+                    1.0 - fraction[axis]
+                };
+                offset += (index[axis] + side) * stride;
+                stride *= n[axis];
+            }
+            value += weight * self.values[offset];
+        }
+        value
+    }
+
+    /// Smallest spacing between lattice points
+    pub fn spacing(&self) -> f64 {
+        let extent = self.max - self.min;
+        (extent.x() / (self.nx - 1) as f64)
+            .min(extent.y() / (self.ny - 1) as f64)
+            .min(extent.z() / (self.nz - 1) as f64)
+    }
+}
+
+/// Light a medium gives off, as radiance per unit length of the rays going through it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum MediumEmission {
+    Constant(Color),
+    /// Radiance from a solid texture at each point
+    Texture(TextureEnum),
+    /// Grid values scaling a color
+    Grid(Grid, Color),
+    /// A grid of temperatures (K) glowing like blackbodies, see `blackbody_emission`,
+    /// scaled by an intensity
+    Blackbody(Grid, f64),
+}
+
+impl MediumEmission {
+    pub fn value(&self, p: &Point3) -> Color {
+        match self {
+            MediumEmission::Constant(c) => *c,
+            MediumEmission::Texture(t) => t.value(0.0, 0.0, p),
+            MediumEmission::Grid(grid, color) => *color * grid.value(p),
+            MediumEmission::Blackbody(grid, intensity) => {
+                blackbody_emission(grid.value(p)) * *intensity
+            }
+        }
+    }
+
+    /// Number of points to take along `distance` to integrate the emission: one if it is
+    /// the same everywhere, a few per grid cell for grids
+    pub fn steps(&self, distance: f64) -> usize {
+        match self {
+            MediumEmission::Constant(_) => 1,
+            MediumEmission::Texture(_) => 16,
+            MediumEmission::Grid(grid, _) | MediumEmission::Blackbody(grid, _) => {
+                ((2.0 * distance / grid.spacing()).ceil() as usize).clamp(1, 256)
+            }
+        }
+    }
+}
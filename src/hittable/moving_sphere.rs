@@ -54,6 +54,7 @@ impl Hittable for MovingSphere {
                 record.p = r.at(record.t);
                 let outward_normal = (record.p - self.center(r.time)) / self.radius;
                 self.set_front_face(r, &outward_normal, record);
+                record.dpdu = super::get_sphere_dpdu(&outward_normal);
                 record.material = Some(self.material.clone());
                 return true;
             }
@@ -63,6 +64,7 @@ impl Hittable for MovingSphere {
                 record.p = r.at(record.t);
                 let outward_normal = (record.p - self.center(r.time)) / self.radius;
                 self.set_front_face(r, &outward_normal, record);
+                record.dpdu = super::get_sphere_dpdu(&outward_normal);
                 record.material = Some(self.material.clone());
                 return true;
             }
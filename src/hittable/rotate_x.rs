@@ -1,7 +1,7 @@
-use super::{HitRecord, Hittable, HittableEnum, AABB};
+use super::{HitRecord, Hittable, HittableEnum, MediumSegment, AABB};
 use crate::degrees_to_radians;
 use crate::ray::Ray;
-use crate::vec3::Vec3;
+use crate::vec3::{Color, Vec3};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -52,6 +52,24 @@ impl RotateX {
     }
 }
 
+impl RotateX {
+    fn to_object(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            v.x(),
+            self.cos_theta * v.y() + self.sin_theta * v.z(),
+            -self.sin_theta * v.y() + self.cos_theta * v.z(),
+        )
+    }
+
+    fn to_world(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            v.x(),
+            self.cos_theta * v.y() - self.sin_theta * v.z(),
+            self.sin_theta * v.y() + self.cos_theta * v.z(),
+        )
+    }
+}
+
 impl Hittable for RotateX {
     fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
         let origin = r.origin;
@@ -79,7 +97,9 @@ impl Hittable for RotateX {
         normal[2] = self.sin_theta * rec.normal[1] + self.cos_theta * rec.normal[2];
 
         rec.p = p;
-        self.set_front_face(&rotated_r, &normal, rec);
+        // The inner hit already faced the normal against the ray and set front_face
+        rec.normal = normal;
+        rec.dpdu = self.to_world(&rec.dpdu);
 
         true
     }
@@ -88,4 +108,65 @@ impl Hittable for RotateX {
         *output_box = self.bbox.clone();
         self.hasbox
     }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f64 {
+        self.ptr
+            .pdf_value(&self.to_object(origin), &self.to_object(direction))
+    }
+
+    fn random(&self, origin: &Vec3) -> Vec3 {
+        self.to_world(&self.ptr.random(&self.to_object(origin)))
+    }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        let mut inner = Vec::new();
+        self.ptr.lights(&mut inner);
+        let angle = self.sin_theta.atan2(self.cos_theta).to_degrees();
+        lights.extend(
+            inner
+                .into_iter()
+                .map(|light| HittableEnum::RotateX(Box::new(RotateX::new(light, angle)))),
+        );
+    }
+
+    fn power(&self) -> f64 {
+        self.ptr.power()
+    }
+
+    fn area(&self) -> f64 {
+        self.ptr.area()
+    }
+
+    fn sample_surface(&self) -> (Vec3, Vec3) {
+        let (point, normal) = self.ptr.sample_surface();
+        (self.to_world(&point), self.to_world(&normal))
+    }
+
+    fn media(&self, r: &Ray, t_min: f64, t_max: f64, segments: &mut Vec<MediumSegment>) {
+        let rotated_r = Ray::new(
+            &self.to_object(&r.origin),
+            &self.to_object(&r.direction),
+            r.time,
+        );
+        self.ptr.media(&rotated_r, t_min, t_max, segments);
+    }
+
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let rotated_r = Ray::new(
+            &self.to_object(&r.origin),
+            &self.to_object(&r.direction),
+            r.time,
+        );
+        self.ptr.transmittance(&rotated_r, t_min, t_max)
+    }
+
+    fn glows(&self) -> bool {
+        self.ptr.glows()
+    }
+
+    fn emission(&self, r: &Ray, t_min: f64, t_max: f64) -> Color {
+        // Keeps the interior, which dims the emission
+        let rotated_r = r.spawn(&self.to_object(&r.origin), &self.to_object(&r.direction));
+        self.ptr.emission(&rotated_r, t_min, t_max)
+    }
 }
@@ -1,8 +1,13 @@
-use crate::hittable::{HitRecord, Hittable, AABB};
+use crate::hittable::{HitRecord, Hittable, HittableEnum, AABB};
 use crate::material::MaterialEnum;
+use crate::onb::Onb;
+use crate::random;
 use crate::ray;
+use crate::spectrum::luminance;
 use crate::vec3;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sphere {
@@ -21,6 +26,20 @@ impl Sphere {
     }
 }
 
+/// Direction within the cone subtended by a sphere of `radius` at squared distance
+/// `distance_squared`, around +z
+fn random_to_sphere(radius: f64, distance_squared: f64) -> vec3::Vec3 {
+    let mut rng = random::rng();
+    let r1: f64 = rng.gen_range(0.0..1.0);
+    let r2: f64 = rng.gen_range(0.0..1.0);
+    let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
+
+    let phi = 2.0 * PI * r1;
+    let x = phi.cos() * (1.0 - z * z).sqrt();
+    let y = phi.sin() * (1.0 - z * z).sqrt();
+    vec3::Vec3::new(x, y, z)
+}
+
 impl Hittable for Sphere {
     fn hit(&self, r: &ray::Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
         let oc = r.origin - self.center;
@@ -40,6 +59,7 @@ impl Hittable for Sphere {
                 record.v = sphere_v;
                 let outward_normal = (record.p - self.center) / self.radius;
                 self.set_front_face(r, &outward_normal, record);
+                record.dpdu = super::get_sphere_dpdu(&outward_normal);
                 record.material = Some(self.material.clone());
                 return true;
             }
@@ -53,6 +73,7 @@ impl Hittable for Sphere {
                 record.v = sphere_v;
                 let outward_normal = (record.p - self.center) / self.radius;
                 self.set_front_face(r, &outward_normal, record);
+                record.dpdu = super::get_sphere_dpdu(&outward_normal);
                 record.material = Some(self.material.clone());
                 return true;
             }
@@ -69,4 +90,56 @@ impl Hittable for Sphere {
         *output_box = box0;
         true
     }
+
+    /// Samples the cone of directions towards the sphere, which must not contain `origin`
+    fn pdf_value(&self, origin: &vec3::Point3, direction: &vec3::Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(
+            &ray::Ray::new(origin, direction, 0.0),
+            0.001,
+            f64::INFINITY,
+            &mut rec,
+        ) {
+            return 0.0;
+        }
+        let distance_squared = (self.center - *origin).power();
+        if distance_squared <= self.radius * self.radius {
+            return 0.0;
+        }
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: &vec3::Point3) -> vec3::Vec3 {
+        let direction = self.center - *origin;
+        let distance_squared = direction.power();
+        if distance_squared <= self.radius * self.radius {
+            return direction;
+        }
+        let uvw = Onb::build_from_w(&direction);
+        uvw.local(&random_to_sphere(self.radius, distance_squared))
+    }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        if self.material.is_emissive() {
+            lights.push(HittableEnum::Sphere(self.clone()));
+        }
+    }
+
+    /// With the radiance at the top of the sphere
+    fn power(&self) -> f64 {
+        let top = self.center + vec3::Vec3::new(0.0, self.radius, 0.0);
+        let area = 4.0 * PI * self.radius * self.radius;
+        PI * area * luminance(&self.material.emitted(0.5, 1.0, &top))
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    fn sample_surface(&self) -> (vec3::Point3, vec3::Vec3) {
+        let normal = vec3::Vec3::rand_unit_vector();
+        (self.center + self.radius * normal, normal)
+    }
 }
@@ -1,5 +1,5 @@
-use super::{HitRecord, Hittable, HittableEnum, AABB};
-use crate::vec3::Vec3;
+use super::{HitRecord, Hittable, HittableEnum, MediumSegment, AABB};
+use crate::vec3::{Color, Point3, Vec3};
 
 use serde::{Deserialize, Serialize};
 use std::boxed::Box;
@@ -26,9 +26,8 @@ impl Hittable for Translation {
             return false;
         }
 
-        let normal = rec.normal;
+        // Moving the hit keeps its normal and front_face
         rec.p += self.offset;
-        self.set_front_face(&moved_r, &normal, rec);
         true
     }
 
@@ -44,4 +43,61 @@ impl Hittable for Translation {
 
         true
     }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        self.ptr.pdf_value(&(*origin - self.offset), direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.ptr.random(&(*origin - self.offset))
+    }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        let mut inner = Vec::new();
+        self.ptr.lights(&mut inner);
+        lights.extend(
+            inner
+                .into_iter()
+                .map(|light| HittableEnum::Translation(Translation::new(light, self.offset))),
+        );
+    }
+
+    fn power(&self) -> f64 {
+        self.ptr.power()
+    }
+
+    fn area(&self) -> f64 {
+        self.ptr.area()
+    }
+
+    fn sample_surface(&self) -> (Point3, Vec3) {
+        let (point, normal) = self.ptr.sample_surface();
+        (point + self.offset, normal)
+    }
+
+    fn media(
+        &self,
+        r: &crate::ray::Ray,
+        t_min: f64,
+        t_max: f64,
+        segments: &mut Vec<MediumSegment>,
+    ) {
+        let moved_r = crate::ray::Ray::new(&(r.origin - self.offset), &r.direction, r.time);
+        self.ptr.media(&moved_r, t_min, t_max, segments);
+    }
+
+    fn transmittance(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> f64 {
+        let moved_r = crate::ray::Ray::new(&(r.origin - self.offset), &r.direction, r.time);
+        self.ptr.transmittance(&moved_r, t_min, t_max)
+    }
+
+    fn glows(&self) -> bool {
+        self.ptr.glows()
+    }
+
+    fn emission(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Color {
+        // Keeps the interior, which dims the emission
+        let moved_r = r.spawn(&(r.origin - self.offset), &r.direction);
+        self.ptr.emission(&moved_r, t_min, t_max)
+    }
 }
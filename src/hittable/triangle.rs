@@ -0,0 +1,131 @@
+use super::{HitRecord, Hittable, HittableEnum, AABB};
+use crate::material::MaterialEnum;
+use crate::random;
+use crate::ray::Ray;
+use crate::spectrum::luminance;
+use crate::vec3::{Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Triangle {
+    v0: Point3,
+    v1: Point3,
+    v2: Point3,
+    material: MaterialEnum,
+}
+
+impl Triangle {
+    /// The front face is the one from which `v0`, `v1`, `v2` appear counterclockwise
+    pub fn new(v0: &Point3, v1: &Point3, v2: &Point3, material: MaterialEnum) -> Self {
+        Self {
+            v0: *v0,
+            v1: *v1,
+            v2: *v2,
+            material,
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    /// Möller-Trumbore intersection, with the barycentric coordinates as texture coordinates
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let edge1 = self.v1 - self.v0;
+        let edge2 = self.v2 - self.v0;
+        let pvec = r.direction.cross(&edge2);
+        let det = edge1.dot(&pvec);
+        if det.abs() < 1e-12 {
+            return false;
+        }
+        let inv_det = 1.0 / det;
+
+        let tvec = r.origin - self.v0;
+        let u = tvec.dot(&pvec) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return false;
+        }
+        let qvec = tvec.cross(&edge1);
+        let v = r.direction.dot(&qvec) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return false;
+        }
+        let t = edge2.dot(&qvec) * inv_det;
+        if t < t_min || t > t_max {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.u = u;
+        rec.v = v;
+        let outward_normal = edge1.cross(&edge2).unit();
+        self.set_front_face(r, &outward_normal, rec);
+        rec.dpdu = edge1;
+        rec.material = Some(self.material.clone());
+        true
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64, output_box: &mut AABB) -> bool {
+        // Padded so that axis-aligned triangles don't get a flat box
+        let mut min = self.v0;
+        let mut max = self.v0;
+        for v in [&self.v1, &self.v2] {
+            for c in 0..3 {
+                min[c] = min[c].min(v[c]);
+                max[c] = max[c].max(v[c]);
+            }
+        }
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+        *output_box = AABB::new(&(min - padding), &(max + padding));
+        true
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(
+            &Ray::new(origin, direction, 0.0),
+            0.001,
+            f64::INFINITY,
+            &mut rec,
+        ) {
+            return 0.0;
+        }
+        let distance_squared = rec.t * rec.t * direction.power();
+        let cosine = (direction.dot(&rec.normal) / direction.len()).abs();
+        distance_squared / (cosine * self.area())
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        let mut rng = random::rng();
+        let r1: f64 = rng.gen_range(0.0..1.0);
+        let r2: f64 = rng.gen_range(0.0..1.0);
+        // Uniform over the area
+        let s = r1.sqrt();
+        let random_point = (1.0 - s) * self.v0 + s * (1.0 - r2) * self.v1 + s * r2 * self.v2;
+        random_point - *origin
+    }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        if self.material.is_emissive() {
+            lights.push(HittableEnum::Triangle(self.clone()));
+        }
+    }
+
+    /// Both sides emit, with the radiance at the centroid
+    fn power(&self) -> f64 {
+        let centroid = (self.v0 + self.v1 + self.v2) / 3.0;
+        let radiance = self.material.emitted(1.0 / 3.0, 1.0 / 3.0, &centroid);
+        2.0 * PI * self.area() * luminance(&radiance)
+    }
+
+    fn area(&self) -> f64 {
+        (self.v1 - self.v0).cross(&(self.v2 - self.v0)).len() / 2.0
+    }
+
+    /// The normal of the front face
+    fn sample_surface(&self) -> (Point3, Vec3) {
+        let normal = (self.v1 - self.v0).cross(&(self.v2 - self.v0)).unit();
+        (self.random(&Point3::zero()), normal)
+    }
+}
@@ -1,7 +1,11 @@
-use super::{HitRecord, Hittable, AABB};
+use super::{HitRecord, Hittable, HittableEnum, AABB};
 use crate::material::MaterialEnum;
-use crate::vec3::Vec3;
+use crate::random;
+use crate::spectrum::luminance;
+use crate::vec3::{Point3, Vec3};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct XZRect {
@@ -43,6 +47,7 @@ impl Hittable for XZRect {
         rec.t = t;
         let outward_normal = Vec3::new(0.0, 1.0, 0.0);
         self.set_front_face(r, &outward_normal, rec);
+        rec.dpdu = Vec3::new(1.0, 0.0, 0.0);
         rec.material = Some(self.mp.clone());
         rec.p = r.at(t);
         true
@@ -56,4 +61,51 @@ impl Hittable for XZRect {
         *output_box = bbox;
         true
     }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(
+            &crate::ray::Ray::new(origin, direction, 0.0),
+            0.001,
+            f64::INFINITY,
+            &mut rec,
+        ) {
+            return 0.0;
+        }
+        let area = (self.x1 - self.x0) * (self.z1 - self.z0);
+        let distance_squared = rec.t * rec.t * direction.power();
+        let cosine = (direction.dot(&rec.normal) / direction.len()).abs();
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        let mut rng = random::rng();
+        let random_point = Point3::new(
+            rng.gen_range(self.x0..self.x1),
+            self.k,
+            rng.gen_range(self.z0..self.z1),
+        );
+        random_point - *origin
+    }
+
+    fn lights(&self, lights: &mut Vec<HittableEnum>) {
+        if self.mp.is_emissive() {
+            lights.push(HittableEnum::XZRect(self.clone()));
+        }
+    }
+
+    /// Both sides emit, with the radiance at the center
+    fn power(&self) -> f64 {
+        let area = (self.x1 - self.x0) * (self.z1 - self.z0);
+        let center = Point3::new((self.x0 + self.x1) / 2.0, self.k, (self.z0 + self.z1) / 2.0);
+        2.0 * PI * area * luminance(&self.mp.emitted(0.5, 0.5, &center))
+    }
+
+    fn area(&self) -> f64 {
+        (self.x1 - self.x0) * (self.z1 - self.z0)
+    }
+
+    fn sample_surface(&self) -> (Point3, Vec3) {
+        (self.random(&Point3::zero()), Vec3::new(0.0, 1.0, 0.0))
+    }
 }
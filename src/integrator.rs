@@ -0,0 +1,269 @@
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::{Film, Splat};
+use crate::hittable::{Hittable, HittableEnum, AABB};
+use crate::light::LightSampler;
+use crate::ray::Ray;
+use crate::vec3::{Color, Point3};
+use std::str::FromStr;
+
+pub mod path_tracer;
+pub use path_tracer::{LightSampling, PathTracer};
+
+pub mod bdpt;
+pub use bdpt::Bdpt;
+
+pub mod volume_path;
+pub use volume_path::VolumePathTracer;
+
+pub mod photon_map;
+pub use photon_map::{Photon, PhotonMap};
+
+pub mod photon_mapping;
+pub use photon_mapping::PhotonMapping;
+
+pub mod mlt;
+pub use mlt::Mlt;
+
+pub mod albedo;
+pub use albedo::Albedo;
+
+pub mod ambient_occlusion;
+pub use ambient_occlusion::AmbientOcclusion;
+
+pub mod bvh_heatmap;
+pub use bvh_heatmap::BvhHeatmap;
+
+pub mod depth;
+pub use depth::Depth;
+
+pub mod normals;
+pub use normals::Normals;
+
+pub mod uv;
+pub use uv::Uv;
+
+pub trait Integrator {
+    /// Color seen along the ray `r` from `camera`, with statistics about the path traced
+    /// for it. `lights` holds the emitters of `world`, see `light::collect_lights`.
+    /// Light the sample finds for other pixels goes to `splats`.
+    fn color(
+        &self,
+        r: &Ray,
+        camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats);
+}
+
+/// Photon mapping and Metropolis light transport render whole images only, see
+/// `IntegratorEnum::render`
+#[derive(Debug, Clone)]
+pub enum IntegratorEnum {
+    PathTracer(PathTracer),
+    Bdpt(Bdpt),
+    VolumePathTracer(VolumePathTracer),
+    PhotonMapping(PhotonMapping),
+    Mlt(Mlt),
+    Normals(Normals),
+    Uv(Uv),
+    Depth(Depth),
+    Albedo(Albedo),
+    AmbientOcclusion(AmbientOcclusion),
+    BvhHeatmap(BvhHeatmap),
+}
+
+impl Integrator for IntegratorEnum {
+    fn color(
+        &self,
+        r: &Ray,
+        camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        match self {
+            IntegratorEnum::PathTracer(p) => p.color(r, camera, background, world, lights, splats),
+            IntegratorEnum::Bdpt(b) => b.color(r, camera, background, world, lights, splats),
+            IntegratorEnum::VolumePathTracer(v) => {
+                v.color(r, camera, background, world, lights, splats)
+            }
+            IntegratorEnum::PhotonMapping(_) => {
+                panic!("Photon mapping renders whole images, see IntegratorEnum::render")
+            }
+            IntegratorEnum::Mlt(_) => {
+                panic!(
+                    "Metropolis light transport renders whole images, see IntegratorEnum::render"
+                )
+            }
+            IntegratorEnum::Normals(n) => n.color(r, camera, background, world, lights, splats),
+            IntegratorEnum::Uv(u) => u.color(r, camera, background, world, lights, splats),
+            IntegratorEnum::Depth(d) => d.color(r, camera, background, world, lights, splats),
+            IntegratorEnum::Albedo(a) => a.color(r, camera, background, world, lights, splats),
+            IntegratorEnum::AmbientOcclusion(ao) => {
+                ao.color(r, camera, background, world, lights, splats)
+            }
+            IntegratorEnum::BvhHeatmap(h) => h.color(r, camera, background, world, lights, splats),
+        }
+    }
+}
+
+impl IntegratorEnum {
+    /// Renders a `width` by `height` image at once, with a single sample per pixel, for
+    /// integrators that can't work a sample at a time. None for the others.
+    pub fn render(
+        &self,
+        camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        width: usize,
+        height: usize,
+    ) -> Option<Film> {
+        match self {
+            IntegratorEnum::PhotonMapping(p) => {
+                Some(p.render(camera, background, world, lights, width, height))
+            }
+            IntegratorEnum::Mlt(m) => {
+                Some(m.render(camera, background, world, lights, width, height))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Integrators by name, with default settings: "path", "nee" and "mis" path trace with
+/// the corresponding light sampling, "volpath" also samples lights from media, "bdpt"
+/// traces paths from both the camera and the lights, and "sppm" and "mlt" render the
+/// whole image with photon mapping and Metropolis light transport. "normals", "uv",
+/// "depth", "albedo", "ao" and "bvh" visualize the scene for debugging.
+impl FromStr for IntegratorEnum {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let path_tracer = |sampling| {
+            IntegratorEnum::PathTracer(PathTracer::new(sampling, PathSettings::new(100)))
+        };
+        match name {
+            "path" => Ok(path_tracer(LightSampling::Bsdf)),
+            "nee" => Ok(path_tracer(LightSampling::Nee)),
+            "mis" => Ok(path_tracer(LightSampling::Mis)),
+            "volpath" => Ok(IntegratorEnum::VolumePathTracer(VolumePathTracer::new(
+                PathSettings::new(100),
+            ))),
+            "bdpt" => Ok(IntegratorEnum::Bdpt(Bdpt::new(PathSettings::new(100)))),
+            "sppm" => Ok(IntegratorEnum::PhotonMapping(PhotonMapping::new(
+                PathSettings::new(100),
+                64,
+                200_000,
+            ))),
+            "mlt" => Ok(IntegratorEnum::Mlt(Mlt::new(
+                PathTracer::new(LightSampling::Mis, PathSettings::new(100)),
+                100,
+            ))),
+            "normals" => Ok(IntegratorEnum::Normals(Normals)),
+            "uv" => Ok(IntegratorEnum::Uv(Uv)),
+            "depth" => Ok(IntegratorEnum::Depth(Depth::new(1000.0))),
+            "albedo" => Ok(IntegratorEnum::Albedo(Albedo)),
+            "ao" => Ok(IntegratorEnum::AmbientOcclusion(AmbientOcclusion::new(
+                100.0,
+            ))),
+            "bvh" => Ok(IntegratorEnum::BvhHeatmap(BvhHeatmap::new(64))),
+            _ => Err(format!("Unknown integrator: {}", name)),
+        }
+    }
+}
+
+/// What to render, and how
+#[derive(Debug, Clone)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: usize,
+    pub integrator: IntegratorEnum,
+}
+
+/// Limits on the length of traced paths
+#[derive(Debug, Clone, Copy)]
+pub struct PathSettings {
+    pub max_depth: usize,
+    /// Number of bounces after which Russian roulette may end a path
+    pub rr_depth: usize,
+}
+
+impl PathSettings {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            rr_depth: 5,
+        }
+    }
+
+    pub fn with_rr_depth(mut self, rr_depth: usize) -> Self {
+        self.rr_depth = rr_depth;
+        self
+    }
+}
+
+/// Why a path stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// Left the scene
+    Escaped,
+    /// Hit a surface that doesn't scatter, such as a light
+    Absorbed,
+    RussianRoulette,
+    MaxDepth,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PathStats {
+    /// Number of surfaces or media the path hit
+    pub bounces: usize,
+    pub termination: Termination,
+}
+
+impl PathStats {
+    pub fn new(bounces: usize, termination: Termination) -> Self {
+        Self {
+            bounces,
+            termination,
+        }
+    }
+
+    /// Statistics of integrators that stop at the first hit
+    pub fn first_hit(hit: bool) -> Self {
+        if hit {
+            Self::new(1, Termination::MaxDepth)
+        } else {
+            Self::new(0, Termination::Escaped)
+        }
+    }
+}
+
+/// Veach's power heuristic with β = 2, weighting a sample of density `f`
+/// against another strategy of density `g`
+pub fn power_heuristic(f: f64, g: f64) -> f64 {
+    if f.is_infinite() {
+        return 1.0;
+    }
+    let f2 = f * f;
+    let g2 = g * g;
+    if f2 + g2 == 0.0 {
+        return 0.0;
+    }
+    f2 / (f2 + g2)
+}
+
+/// Center and radius of a sphere around `world`, through which light from infinity enters
+pub(crate) fn bounding_sphere(world: &HittableEnum) -> (Point3, f64) {
+    let mut bbox = AABB::new(&Point3::zero(), &Point3::zero());
+    if !world.bounding_box(0.0, 1.0, &mut bbox) {
+        return (Point3::zero(), 1e4);
+    }
+    let center = (bbox.min() + bbox.max()) / 2.0;
+    (center, (bbox.max() - center).len().max(1e-3))
+}
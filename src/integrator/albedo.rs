@@ -0,0 +1,37 @@
+use super::{Integrator, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::LightSampler;
+use crate::material::ScatterRecord;
+use crate::ray::Ray;
+use crate::vec3::Color;
+
+/// Weight of a sampled bounce at the first hit, which averages out to the
+/// fraction of light the surface reflects. Lights and misses are black.
+#[derive(Debug, Clone)]
+pub struct Albedo;
+
+impl Integrator for Albedo {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        _background: &Background,
+        world: &HittableEnum,
+        _lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let mut rec = HitRecord::new();
+        if !world.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            return (Color::zero(), PathStats::first_hit(false));
+        }
+        let mat = rec.material.clone().expect("Material is None");
+        let mut srec = ScatterRecord::new();
+        if !mat.sample(r, &rec, &mut srec) {
+            return (Color::zero(), PathStats::first_hit(true));
+        }
+        (srec.attenuation, PathStats::first_hit(true))
+    }
+}
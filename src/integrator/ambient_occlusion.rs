@@ -0,0 +1,46 @@
+use super::{Integrator, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::LightSampler;
+use crate::ray::Ray;
+use crate::vec3::{Color, Vec3};
+
+/// Fraction of cosine-weighted directions from the first hit that are open
+/// within `radius`
+#[derive(Debug, Clone)]
+pub struct AmbientOcclusion {
+    radius: f64,
+}
+
+impl AmbientOcclusion {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        _background: &Background,
+        world: &HittableEnum,
+        _lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let mut rec = HitRecord::new();
+        if !world.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            return (Color::new(1.0, 1.0, 1.0), PathStats::first_hit(false));
+        }
+
+        let direction = (rec.normal + Vec3::rand_unit_vector()).unit();
+        let occlusion_ray = Ray::new(&rec.p, &direction, r.time);
+        let mut occluder = HitRecord::new();
+        if world.hit(&occlusion_ray, 0.001, self.radius, &mut occluder) {
+            return (Color::zero(), PathStats::first_hit(true));
+        }
+        (Color::new(1.0, 1.0, 1.0), PathStats::first_hit(true))
+    }
+}
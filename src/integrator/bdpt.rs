@@ -0,0 +1,806 @@
+use super::{bounding_sphere, Integrator, PathSettings, PathStats, Termination};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::{Light, LightEnum, LightSampler};
+use crate::material::{InteriorStack, MaterialEnum, ScatterRecord};
+use crate::random;
+use crate::ray::Ray;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// Bidirectional path tracing (Veach): a path from the camera and one from a light are
+/// connected at every pair of their vertices, and the strategies that can produce the
+/// same path weighted with the power heuristic. Light paths connected straight to the
+/// camera land anywhere on the film, as splats.
+///
+/// Lights are picked by power through `LightSampler::sample_emission`, at both ends, so
+/// that connections to the lights and paths leaving them agree. Dispersive materials
+/// narrow each subpath down to its own wavelength, which connections ignore.
+#[derive(Debug, Clone)]
+pub struct Bdpt {
+    settings: PathSettings,
+}
+
+impl Bdpt {
+    pub fn new(settings: PathSettings) -> Self {
+        Self { settings }
+    }
+}
+
+/// The scene a sample is traced in
+struct Context<'a> {
+    camera: &'a Camera,
+    background: &'a Background,
+    world: &'a HittableEnum,
+    sampler: &'a LightSampler,
+    lights: &'a [LightEnum],
+    /// Bounding sphere of the scene, through which light from infinity enters
+    center: Point3,
+    radius: f64,
+    time: f64,
+}
+
+impl Context<'_> {
+    /// Solid angle density with which the lights at infinity send light arriving from
+    /// `direction`, picking the light included
+    fn infinite_density(&self, direction: &Vec3) -> f64 {
+        self.lights
+            .iter()
+            .enumerate()
+            .filter(|(_, light)| light.is_infinite())
+            .map(|(i, light)| light.pdf(&self.center, direction) * self.sampler.pmf_emission(i))
+            .sum()
+    }
+
+    /// Area density of the point a light sample or emission sample picks on `light`
+    fn position_density(&self, light: &LightEnum) -> f64 {
+        match light {
+            LightEnum::Area(area) => 1.0 / area.area(),
+            _ => 1.0,
+        }
+    }
+
+    /// Whether nothing blocks `ray` over `distance`
+    fn unoccluded(&self, ray: &Ray, distance: f64) -> bool {
+        !self
+            .world
+            .hit(ray, 0.001, distance * (1.0 - 1e-6), &mut HitRecord::new())
+    }
+}
+
+/// A hit where light scatters, as seen by the ray that found it
+#[derive(Clone)]
+struct Scattering {
+    ray: Ray,
+    rec: HitRecord,
+    material: MaterialEnum,
+    /// Interior of rays leaving on the other side of the surface
+    across: InteriorStack,
+    /// Scattered by a volume, where there is no cosine
+    medium: bool,
+    /// Index of the area light that was hit, for emissive materials
+    light: Option<usize>,
+}
+
+impl Scattering {
+    /// The ray arriving from `w`, pointing away from the hit, and the hit as it sees it
+    fn seen_from(&self, w: &Vec3) -> (Ray, HitRecord) {
+        let mut ray = self.ray.clone();
+        ray.origin = self.rec.p + *w;
+        ray.direction = -*w;
+        let mut rec = self.rec.clone();
+        if w.dot(&rec.normal) < 0.0 {
+            ray.interior = self.across.clone();
+            rec.normal = -rec.normal;
+            rec.front_face = !rec.front_face;
+        }
+        (ray, rec)
+    }
+
+    /// f |cos| of light arriving from `wi` and leaving towards `wo`, with the cosine of `wi`
+    fn eval(&self, wo: &Vec3, wi: &Vec3) -> Color {
+        let (ray, rec) = self.seen_from(wo);
+        self.material.eval(&ray, &rec, wi)
+    }
+
+    /// Solid angle density of picking `wi` when arriving from `wo`
+    fn pdf(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        let (ray, rec) = self.seen_from(wo);
+        self.material.pdf(&ray, &rec, wi)
+    }
+
+    /// f |cos| of light arriving from `from` and leaving towards `to`, with the cosine
+    /// of `to`, as paths from the lights weigh their bounces
+    fn eval_towards(&self, from: &Vec3, to: &Vec3) -> Color {
+        let f = self.eval(to, from);
+        if self.medium {
+            return f;
+        }
+        let cos_from = self.rec.normal.dot(from).abs();
+        if cos_from == 0.0 {
+            return Color::zero();
+        }
+        f * (self.rec.normal.dot(to).abs() / cos_from)
+    }
+
+    /// Radiance emitted back along the ray that found the hit
+    fn emitted(&self) -> Color {
+        self.material.emitted(self.rec.u, self.rec.v, &self.rec.p)
+            * self
+                .material
+                .emission_falloff(&self.rec, &-self.ray.direction)
+    }
+}
+
+#[derive(Clone)]
+enum VertexKind {
+    Camera,
+    /// A point on the light with this index
+    Light(usize),
+    /// Light from infinitely far away, from the light with this index if it was sampled
+    Infinite(Option<usize>),
+    Surface(Box<Scattering>),
+}
+
+#[derive(Clone)]
+struct Vertex {
+    kind: VertexKind,
+    p: Point3,
+    /// Geometric normal, zero for points in volumes, the camera, points and lights at
+    /// infinity
+    n: Vec3,
+    /// Unit vector towards a light at infinity
+    direction: Vec3,
+    /// Weight of the subpath up to this vertex
+    beta: Color,
+    /// Left along a delta lobe
+    delta: bool,
+    /// Area density with which the subpath sampled this vertex, and with which a path
+    /// in the opposite direction would have. Solid angle densities for light at infinity.
+    pdf_fwd: f64,
+    pdf_rev: f64,
+}
+
+impl Vertex {
+    fn endpoint(kind: VertexKind, p: &Point3, n: &Vec3, beta: &Color) -> Self {
+        Self {
+            kind,
+            p: *p,
+            n: *n,
+            direction: Vec3::zero(),
+            beta: *beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn infinite(light: Option<usize>, direction: &Vec3, beta: &Color) -> Self {
+        Self {
+            direction: direction.unit(),
+            ..Self::endpoint(
+                VertexKind::Infinite(light),
+                &Point3::zero(),
+                &Vec3::zero(),
+                beta,
+            )
+        }
+    }
+
+    fn scattering(&self) -> Option<&Scattering> {
+        match &self.kind {
+            VertexKind::Surface(scattering) => Some(scattering),
+            _ => None,
+        }
+    }
+
+    fn is_on_surface(&self) -> bool {
+        self.n != Vec3::zero()
+    }
+
+    fn is_infinite(&self) -> bool {
+        matches!(self.kind, VertexKind::Infinite(_))
+    }
+
+    fn is_delta_light(&self, ctx: &Context) -> bool {
+        match self.kind {
+            VertexKind::Light(i) | VertexKind::Infinite(Some(i)) => ctx.lights[i].is_delta(),
+            _ => false,
+        }
+    }
+
+    /// Unit vector towards `other`
+    fn toward(&self, other: &Vertex) -> Vec3 {
+        if other.is_infinite() {
+            return other.direction;
+        }
+        if self.is_infinite() {
+            return -self.direction;
+        }
+        (other.p - self.p).unit()
+    }
+
+    /// Ray leaving the vertex along `direction`
+    fn leaving(&self, direction: &Vec3, time: f64) -> Ray {
+        match self.scattering() {
+            Some(scattering) => {
+                let (arriving, _) = scattering.seen_from(direction);
+                let mut ray = arriving.spawn(&self.p, direction);
+                ray.time = time;
+                ray
+            }
+            None => Ray::new(&self.p, direction, time),
+        }
+    }
+
+    /// The solid angle density `pdf` of leaving towards `next`, as an area density at `next`
+    fn convert_density(&self, pdf: f64, next: &Vertex) -> f64 {
+        if next.is_infinite() {
+            return pdf;
+        }
+        let w = next.p - self.p;
+        let distance_squared = w.power();
+        if distance_squared == 0.0 {
+            return 0.0;
+        }
+        let mut pdf = pdf / distance_squared;
+        if next.is_on_surface() {
+            pdf *= next.n.dot(&w).abs() / distance_squared.sqrt();
+        }
+        pdf
+    }
+
+    /// Area density with which a path through `prev` and this vertex continues to `next`
+    fn pdf(&self, ctx: &Context, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        let pdf = match &self.kind {
+            VertexKind::Camera => ctx.camera.pdf_direction(&self.toward(next)),
+            VertexKind::Light(_) | VertexKind::Infinite(_) => return self.pdf_light(ctx, next),
+            VertexKind::Surface(scattering) => match prev {
+                Some(prev) => scattering.pdf(&self.toward(prev), &self.toward(next)),
+                None => 0.0,
+            },
+        };
+        self.convert_density(pdf, next)
+    }
+
+    /// Area density with which this vertex, on a light, emits towards `next`
+    fn pdf_light(&self, ctx: &Context, next: &Vertex) -> f64 {
+        if self.is_infinite() {
+            let mut pdf = 1.0 / (PI * ctx.radius * ctx.radius);
+            if next.is_on_surface() {
+                pdf *= next.n.dot(&self.direction).abs();
+            }
+            return pdf;
+        }
+        let light = match &self.kind {
+            VertexKind::Light(i) => *i,
+            VertexKind::Surface(scattering) => match scattering.light {
+                Some(i) => i,
+                None => return 0.0,
+            },
+            _ => return 0.0,
+        };
+        let direction = self.toward(next);
+        let pdf = ctx.lights[light].pdf_emission(&self.p, &self.n, &direction);
+        self.convert_density(pdf, next)
+    }
+
+    /// Density with which a path from the lights starts at this vertex, to continue
+    /// towards `next`: per area, or per solid angle for light at infinity
+    fn pdf_light_origin(&self, ctx: &Context) -> f64 {
+        let light = match &self.kind {
+            VertexKind::Infinite(_) => return ctx.infinite_density(&self.direction),
+            VertexKind::Light(i) => *i,
+            VertexKind::Surface(scattering) => match scattering.light {
+                Some(i) => i,
+                None => return 0.0,
+            },
+            VertexKind::Camera => return 0.0,
+        };
+        ctx.sampler.pmf_emission(light) * ctx.position_density(&ctx.lights[light])
+    }
+
+    /// Radiance emitted towards the previous vertex of a camera path
+    fn emitted(&self, ctx: &Context) -> Color {
+        match &self.kind {
+            VertexKind::Infinite(_) => ctx.background.value(&self.direction),
+            VertexKind::Surface(scattering) if scattering.material.is_emissive() => {
+                scattering.emitted()
+            }
+            _ => Color::zero(),
+        }
+    }
+}
+
+fn max_component(c: &Color) -> f64 {
+    c.x().max(c.y()).max(c.z())
+}
+
+impl Bdpt {
+    /// Extends `path` with the hits of `ray`, which carries `beta` and was sampled with the
+    /// solid angle density `pdf`. Paths from the lights (`adjoint`) don't keep the point
+    /// where they leave the scene. Paths from the camera add the light of glowing media
+    /// they go through to `glow`, which no other strategy finds.
+    #[allow(clippy::too_many_arguments)]
+    fn random_walk(
+        &self,
+        ctx: &Context,
+        ray: Ray,
+        beta: Color,
+        pdf: f64,
+        adjoint: bool,
+        max_vertices: usize,
+        path: &mut Vec<Vertex>,
+        glow: &mut Color,
+    ) -> Termination {
+        let mut rng = random::rng();
+        let mut ray = ray;
+        let mut beta = beta;
+        let mut pdf_fwd = pdf;
+        let reference = max_component(&beta);
+
+        while path.len() < max_vertices {
+            let mut rec = HitRecord::new();
+            let hit = ctx.world.hit(&ray, 0.001, f64::INFINITY, &mut rec);
+            if !adjoint {
+                *glow += beta * ray.glow(ctx.world, hit, &rec);
+            }
+            if !hit {
+                if !adjoint {
+                    let mut vertex = Vertex::infinite(None, &ray.direction, &beta);
+                    vertex.pdf_fwd = pdf_fwd;
+                    path.push(vertex);
+                }
+                return Termination::Escaped;
+            }
+            beta = beta * ray.through_interior(&mut rec);
+            let material = match rec.material.clone() {
+                Some(material) => material,
+                None => panic!("Material is None"),
+            };
+
+            let medium = matches!(material, MaterialEnum::Isotropic(_));
+            let light = if material.is_emissive() && !adjoint {
+                ctx.sampler.area_light_at(&ray, rec.t)
+            } else {
+                None
+            };
+            let scattering = Scattering {
+                ray: ray.clone(),
+                rec: rec.clone(),
+                material: material.clone(),
+                across: material.interior_across(&ray, &rec),
+                medium,
+                light,
+            };
+            let normal = if medium { Vec3::zero() } else { rec.normal };
+            let mut vertex = Vertex::endpoint(
+                VertexKind::Surface(Box::new(scattering)),
+                &rec.p,
+                &normal,
+                &beta,
+            );
+            let current = path.len();
+            vertex.pdf_fwd = path[current - 1].convert_density(pdf_fwd, &vertex);
+            path.push(vertex);
+            if path.len() >= max_vertices {
+                break;
+            }
+
+            let mut srec = ScatterRecord::new();
+            if !material.sample(&ray, &rec, &mut srec) {
+                return Termination::Absorbed;
+            }
+            let wo = -ray.direction.unit();
+            let wi = srec.scattered.direction.unit();
+            // Lobes too narrow for their densities to be finite count as delta lobes
+            let pdf_rev = if srec.is_delta || !srec.pdf.is_finite() {
+                path[current].delta = true;
+                pdf_fwd = 0.0;
+                beta = beta * srec.attenuation;
+                0.0
+            } else {
+                let scattering = path[current].scattering().unwrap();
+                pdf_fwd = srec.pdf;
+                // Light paths weigh the bounce by the BSDF with its directions swapped
+                beta = beta
+                    * if adjoint {
+                        scattering.eval_towards(&wo, &wi) / srec.pdf
+                    } else {
+                        srec.attenuation
+                    };
+                let pdf_rev = scattering.pdf(&wi, &wo);
+                if pdf_rev.is_finite() {
+                    pdf_rev
+                } else {
+                    0.0
+                }
+            };
+            if !max_component(&beta).is_finite() {
+                return Termination::Absorbed;
+            }
+            let pdf_prev = path[current].convert_density(pdf_rev, &path[current - 1]);
+            path[current - 1].pdf_rev = pdf_prev;
+            if beta == Color::zero() {
+                return Termination::Absorbed;
+            }
+
+            if current >= self.settings.rr_depth {
+                let survival = (max_component(&beta) / reference).min(0.95);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    return Termination::RussianRoulette;
+                }
+                beta /= survival;
+            }
+            ray = srec.scattered;
+        }
+        Termination::MaxDepth
+    }
+
+    /// The path, how it ended and the light of the glowing media along it
+    fn camera_subpath(&self, ctx: &Context, r: &Ray) -> (Vec<Vertex>, Termination, Color) {
+        let mut path = vec![Vertex::endpoint(
+            VertexKind::Camera,
+            &r.origin,
+            &Vec3::zero(),
+            &Color::new(1.0, 1.0, 1.0),
+        )];
+        let pdf = ctx.camera.pdf_direction(&r.direction);
+        let mut glow = Color::zero();
+        let termination = self.random_walk(
+            ctx,
+            r.clone(),
+            Color::new(1.0, 1.0, 1.0),
+            pdf,
+            false,
+            self.settings.max_depth + 2,
+            &mut path,
+            &mut glow,
+        );
+        (path, termination, glow)
+    }
+
+    fn light_subpath(&self, ctx: &Context) -> Vec<Vertex> {
+        let mut path = Vec::new();
+        if ctx.lights.is_empty() {
+            return path;
+        }
+        let (index, pmf) = match ctx.sampler.sample_emission() {
+            Some(choice) => choice,
+            None => return path,
+        };
+        let light = &ctx.lights[index];
+        let emission = match light.sample_emission(&ctx.center, ctx.radius) {
+            Some(e) if e.pdf_position > 0.0 && e.pdf_direction > 0.0 => e,
+            _ => return path,
+        };
+        if emission.radiance == Color::zero() {
+            return path;
+        }
+
+        let cos_theta = if emission.normal == Vec3::zero() {
+            1.0
+        } else {
+            emission.normal.dot(&emission.direction).abs()
+        };
+        let pdf_origin = pmf * emission.pdf_position;
+        let beta = emission.radiance * cos_theta / (pdf_origin * emission.pdf_direction);
+        let origin = if light.is_infinite() {
+            let mut vertex =
+                Vertex::infinite(Some(index), &-emission.direction, &emission.radiance);
+            vertex.p = emission.origin;
+            vertex.pdf_fwd = ctx.infinite_density(&vertex.direction);
+            vertex
+        } else {
+            let mut vertex = Vertex::endpoint(
+                VertexKind::Light(index),
+                &emission.origin,
+                &emission.normal,
+                &emission.radiance,
+            );
+            vertex.pdf_fwd = pdf_origin;
+            vertex
+        };
+        path.push(origin);
+
+        let ray = Ray::new(&emission.origin, &emission.direction, ctx.time);
+        self.random_walk(
+            ctx,
+            ray,
+            beta,
+            emission.pdf_direction,
+            true,
+            self.settings.max_depth + 1,
+            &mut path,
+            &mut Color::zero(),
+        );
+
+        // From infinity the first hit is found through the disk, by its area density
+        if light.is_infinite() && path.len() > 1 {
+            let mut pdf = emission.pdf_position;
+            if path[1].is_on_surface() {
+                pdf *= path[1].n.dot(&emission.direction).abs();
+            }
+            path[1].pdf_fwd = pdf;
+        }
+        path
+    }
+
+    /// Contribution of the path made of the first `s` vertices of `light_path` and the
+    /// first `t` of `camera_path` to the pixel, or to `splats` if `t` is 1
+    fn connect(
+        &self,
+        ctx: &Context,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+        splats: &mut Vec<Splat>,
+    ) -> Color {
+        let pt = &camera_path[t - 1];
+        if t > 1 && s != 0 && pt.is_infinite() {
+            return Color::zero();
+        }
+
+        if s == 0 {
+            let radiance = pt.beta * pt.emitted(ctx);
+            if radiance == Color::zero() {
+                return radiance;
+            }
+            return radiance * self.mis_weight(ctx, light_path, camera_path, None, s, t);
+        }
+
+        if t == 1 {
+            let qs = &light_path[s - 1];
+            let scattering = match qs.scattering() {
+                Some(scattering) => scattering,
+                None => return Color::zero(),
+            };
+            let lens = match ctx.camera.sample_lens(&qs.p) {
+                Some(lens) if lens.pdf > 0.0 => lens,
+                _ => return Color::zero(),
+            };
+            let f = scattering.eval_towards(&qs.toward(&light_path[s - 2]), &lens.direction);
+            if f == Color::zero() {
+                return Color::zero();
+            }
+            let ray = qs.leaving(&lens.direction, ctx.time);
+            if !ctx.unoccluded(&ray, lens.distance) {
+                return Color::zero();
+            }
+            let importance = lens.importance / lens.pdf;
+            let sampled = Vertex::endpoint(
+                VertexKind::Camera,
+                &lens.point,
+                &Vec3::zero(),
+                &Color::new(importance, importance, importance),
+            );
+            let radiance = qs.beta * f * sampled.beta * ray.interior.transmittance(lens.distance);
+            let weight = self.mis_weight(ctx, light_path, camera_path, Some(&sampled), s, t);
+            splats.push(Splat {
+                s: lens.s,
+                t: lens.t,
+                color: radiance * weight,
+            });
+            return Color::zero();
+        }
+
+        let scattering = match pt.scattering() {
+            Some(scattering) => scattering,
+            None => return Color::zero(),
+        };
+        let wo = pt.toward(&camera_path[t - 2]);
+
+        if s == 1 {
+            let (index, pmf) = match ctx.sampler.sample_emission() {
+                Some(choice) => choice,
+                None => return Color::zero(),
+            };
+            let light = &ctx.lights[index];
+            let sample = match light.sample(&pt.p) {
+                Some(sample) if sample.pdf > 0.0 => sample,
+                _ => return Color::zero(),
+            };
+            let f = scattering.eval(&wo, &sample.direction);
+            if f == Color::zero() {
+                return Color::zero();
+            }
+            let ray = pt.leaving(&sample.direction, ctx.time);
+            let distance = sample.distance.min(f64::MAX);
+            if !ctx.unoccluded(&ray, distance) {
+                return Color::zero();
+            }
+            let beta = sample.radiance / (sample.pdf * pmf);
+            let mut sampled = if light.is_infinite() {
+                Vertex::infinite(Some(index), &sample.direction, &beta)
+            } else {
+                let p = pt.p + sample.direction * sample.distance;
+                let normal = match light {
+                    LightEnum::Area(area) => {
+                        let mut rec = HitRecord::new();
+                        area.hit(&ray, 0.001, f64::INFINITY, &mut rec);
+                        rec.normal
+                    }
+                    _ => Vec3::zero(),
+                };
+                Vertex::endpoint(VertexKind::Light(index), &p, &normal, &beta)
+            };
+            sampled.pdf_fwd = sampled.pdf_light_origin(ctx);
+            let radiance = pt.beta * f * sampled.beta * ray.interior.transmittance(distance);
+            return radiance * self.mis_weight(ctx, light_path, camera_path, Some(&sampled), s, t);
+        }
+
+        let qs = &light_path[s - 1];
+        let light_scattering = match qs.scattering() {
+            Some(scattering) => scattering,
+            None => return Color::zero(),
+        };
+        let to_light = qs.p - pt.p;
+        let distance = to_light.len();
+        if distance == 0.0 {
+            return Color::zero();
+        }
+        let direction = to_light / distance;
+        let f = scattering.eval(&wo, &direction)
+            * light_scattering.eval_towards(&qs.toward(&light_path[s - 2]), &-direction);
+        if f == Color::zero() {
+            return Color::zero();
+        }
+        let ray = pt.leaving(&direction, ctx.time);
+        if !ctx.unoccluded(&ray, distance) {
+            return Color::zero();
+        }
+        let radiance =
+            qs.beta * f * pt.beta * ray.interior.transmittance(distance) / (distance * distance);
+        radiance * self.mis_weight(ctx, light_path, camera_path, None, s, t)
+    }
+
+    /// Power heuristic weight of the strategy with `s` light and `t` camera vertices,
+    /// against every other that could have produced its path. `sampled` replaces the
+    /// last light vertex if `s` is 1, or the camera vertex if `t` is 1.
+    fn mis_weight(
+        &self,
+        ctx: &Context,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        sampled: Option<&Vertex>,
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        if s + t == 2 {
+            return 1.0;
+        }
+        let qs = match s {
+            0 => None,
+            1 => sampled,
+            _ => Some(&light_path[s - 1]),
+        };
+        let pt = match t {
+            1 => sampled.unwrap(),
+            _ => &camera_path[t - 1],
+        };
+        let qs_minus = if s > 1 {
+            Some(&light_path[s - 2])
+        } else {
+            None
+        };
+        let pt_minus = if t > 1 {
+            Some(&camera_path[t - 2])
+        } else {
+            None
+        };
+
+        // Reverse and forward densities, and delta flags, of the vertices of this path
+        let densities = |v: &Vertex| (v.pdf_rev, v.pdf_fwd, v.delta);
+        let mut camera: Vec<_> = camera_path[..t - 1].iter().map(densities).collect();
+        camera.push(densities(pt));
+        let mut light: Vec<_> = light_path[..s.saturating_sub(1)]
+            .iter()
+            .map(densities)
+            .collect();
+        if let Some(qs) = qs {
+            light.push(densities(qs));
+        }
+
+        // Only light from the light list can be found from both ends
+        let pt_rev = match qs {
+            Some(qs) => qs.pdf(ctx, qs_minus, pt),
+            None => pt.pdf_light_origin(ctx),
+        };
+        if qs.is_none() && pt_rev == 0.0 {
+            return 1.0;
+        }
+
+        // The connection itself is never along a delta lobe
+        camera[t - 1] = (pt_rev, camera[t - 1].1, false);
+        if let Some(pt_minus) = pt_minus {
+            camera[t - 2].0 = match qs {
+                Some(qs) => pt.pdf(ctx, Some(qs), pt_minus),
+                None => pt.pdf_light(ctx, pt_minus),
+            };
+        }
+        if let Some(qs) = qs {
+            light[s - 1] = (pt.pdf(ctx, pt_minus, qs), light[s - 1].1, false);
+        }
+        if let (Some(qs), Some(qs_minus)) = (qs, qs_minus) {
+            light[s - 2].0 = qs.pdf(ctx, Some(pt), qs_minus);
+        }
+
+        let remap = |pdf: f64| if pdf != 0.0 { pdf } else { 1.0 };
+        let mut sum = 0.0;
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            ratio *= remap(camera[i].0) / remap(camera[i].1);
+            if !camera[i].2 && !camera[i - 1].2 {
+                sum += ratio * ratio;
+            }
+        }
+        ratio = 1.0;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].0) / remap(light[i].1);
+            let delta_before = match i {
+                0 => qs
+                    .is_some_and(|qs| if s == 1 { qs } else { &light_path[0] }.is_delta_light(ctx)),
+                _ => light[i - 1].2,
+            };
+            if !light[i].2 && !delta_before {
+                sum += ratio * ratio;
+            }
+        }
+        1.0 / (1.0 + sum)
+    }
+}
+
+impl Integrator for Bdpt {
+    fn color(
+        &self,
+        r: &Ray,
+        camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let (center, radius) = bounding_sphere(world);
+        let ctx = Context {
+            camera,
+            background,
+            world,
+            sampler: lights,
+            lights: lights.lights(),
+            center,
+            radius,
+            time: r.time,
+        };
+
+        let (camera_path, termination, glow) = self.camera_subpath(&ctx, r);
+        let light_path = self.light_subpath(&ctx);
+        let max_s = if ctx.lights.is_empty() {
+            0
+        } else {
+            light_path.len().max(1)
+        };
+        let mut radiance = glow;
+        for t in 1..=camera_path.len() {
+            for s in 0..=max_s {
+                let vertices = s + t;
+                if (s == 1 && t == 1) || vertices < 2 || vertices - 2 > self.settings.max_depth {
+                    continue;
+                }
+                if s > 1 && s > light_path.len() {
+                    continue;
+                }
+                radiance += self.connect(&ctx, &light_path, &camera_path, s, t, splats);
+            }
+        }
+
+        let bounces = camera_path
+            .iter()
+            .filter(|v| v.scattering().is_some())
+            .count();
+        (radiance, PathStats::new(bounces, termination))
+    }
+}
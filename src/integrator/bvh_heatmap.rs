@@ -0,0 +1,45 @@
+use super::{Integrator, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{bvh, HitRecord, Hittable, HittableEnum};
+use crate::light::LightSampler;
+use crate::ray::Ray;
+use crate::vec3::Color;
+
+/// Number of BVH nodes visited to find the first hit, from blue for none through green
+/// to red for `max_visits` or more
+#[derive(Debug, Clone)]
+pub struct BvhHeatmap {
+    max_visits: usize,
+}
+
+impl BvhHeatmap {
+    pub fn new(max_visits: usize) -> Self {
+        Self { max_visits }
+    }
+}
+
+impl Integrator for BvhHeatmap {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        _background: &Background,
+        world: &HittableEnum,
+        _lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        bvh::reset_nodes_visited();
+        let mut rec = HitRecord::new();
+        let hit = world.hit(r, 0.001, f64::INFINITY, &mut rec);
+        let cost = (bvh::nodes_visited() as f64 / self.max_visits as f64).min(1.0);
+
+        let color = if cost < 0.5 {
+            Color::new(0.0, 2.0 * cost, 1.0 - 2.0 * cost)
+        } else {
+            Color::new(2.0 * cost - 1.0, 2.0 - 2.0 * cost, 0.0)
+        };
+        (color, PathStats::first_hit(hit))
+    }
+}
@@ -0,0 +1,40 @@
+use super::{Integrator, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::LightSampler;
+use crate::ray::Ray;
+use crate::vec3::Color;
+
+/// Distance to the first hit in grayscale, from black at the camera to white at
+/// `max_distance` and beyond
+#[derive(Debug, Clone)]
+pub struct Depth {
+    max_distance: f64,
+}
+
+impl Depth {
+    pub fn new(max_distance: f64) -> Self {
+        Self { max_distance }
+    }
+}
+
+impl Integrator for Depth {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        _background: &Background,
+        world: &HittableEnum,
+        _lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let mut rec = HitRecord::new();
+        if !world.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            return (Color::new(1.0, 1.0, 1.0), PathStats::first_hit(false));
+        }
+        let depth = (rec.t * r.direction.len() / self.max_distance).min(1.0);
+        (Color::new(depth, depth, depth), PathStats::first_hit(true))
+    }
+}
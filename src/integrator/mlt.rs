@@ -0,0 +1,338 @@
+use super::{Integrator, PathTracer};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::{Film, Splat};
+use crate::hittable::HittableEnum;
+use crate::light::LightSampler;
+use crate::random::{self, SampleSource};
+use crate::spectrum::luminance;
+use crate::vec3::Color;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::thread;
+
+/// Primary sample space Metropolis light transport (Kelemen et al.). The random numbers
+/// a `PathTracer` path is traced from, film position included, are mutated: mostly a
+/// little (small steps), so that paths near a bright one that is hard to find get
+/// explored, and sometimes all anew (large steps). Each chain splats every path it
+/// proposes by its expected contribution. A bootstrap phase of independent paths gives
+/// the image brightness, which the chains alone can't know, and where they start.
+///
+/// Renders the whole image at once rather than a sample at a time, see `render`.
+#[derive(Debug, Clone)]
+pub struct Mlt {
+    tracer: PathTracer,
+    mutations_per_pixel: usize,
+    bootstrap_samples: usize,
+    chains: usize,
+    /// Standard deviation of small steps
+    sigma: f64,
+    large_step_probability: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PrimarySample {
+    value: f64,
+    /// Iteration that last changed the value
+    modified: u64,
+    /// The value and iteration before the current iteration changed them
+    backup: f64,
+    backup_modified: u64,
+}
+
+/// The random numbers of a chain, mutated lazily: each only when the path asks for it
+struct PrimarySamples {
+    rng: StdRng,
+    samples: Vec<PrimarySample>,
+    sigma: f64,
+    large_step_probability: f64,
+    iteration: u64,
+    last_large_step: u64,
+    large_step: bool,
+    index: usize,
+}
+
+impl PrimarySamples {
+    /// Samples that give the same first path for the same seed
+    fn new(seed: u64, sigma: f64, large_step_probability: f64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            samples: Vec::new(),
+            sigma,
+            large_step_probability,
+            iteration: 0,
+            last_large_step: 0,
+            large_step: true,
+            index: 0,
+        }
+    }
+
+    fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = self.rng.gen_range(0.0..1.0) < self.large_step_probability;
+        self.index = 0;
+    }
+
+    fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    fn reject(&mut self) {
+        for sample in &mut self.samples {
+            if sample.modified == self.iteration {
+                sample.value = sample.backup;
+                sample.modified = sample.backup_modified;
+            }
+        }
+        self.iteration -= 1;
+    }
+
+    fn next(&mut self) -> f64 {
+        // Samples never used before start uniform, as small steps alone would keep them
+        // near 0 and rejection sampling waiting for another value
+        if self.index == self.samples.len() {
+            self.samples.push(PrimarySample {
+                value: self.rng.gen_range(0.0..1.0),
+                modified: self.last_large_step,
+                ..PrimarySample::default()
+            });
+        }
+        let sample = &mut self.samples[self.index];
+        self.index += 1;
+
+        // Samples unused since the last large step take the value it gave them
+        if sample.modified < self.last_large_step {
+            sample.value = self.rng.gen_range(0.0..1.0);
+            sample.modified = self.last_large_step;
+        }
+        sample.backup = sample.value;
+        sample.backup_modified = sample.modified;
+
+        if self.large_step {
+            sample.value = self.rng.gen_range(0.0..1.0);
+        } else {
+            // The small steps missed since the sample was last used, at once
+            let steps = (self.iteration - sample.modified) as f64;
+            let u1: f64 = self.rng.gen_range(0.0..1.0);
+            let u2: f64 = self.rng.gen_range(0.0..1.0);
+            let normal = (-2.0 * (1.0 - u1).ln()).sqrt() * (2.0 * PI * u2).cos();
+            sample.value += normal * self.sigma * steps.sqrt();
+            sample.value -= sample.value.floor();
+        }
+        sample.modified = self.iteration;
+        sample.value
+    }
+}
+
+struct SharedSamples(Rc<RefCell<PrimarySamples>>);
+
+impl SampleSource for SharedSamples {
+    fn next_sample(&mut self) -> f64 {
+        self.0.borrow_mut().next()
+    }
+}
+
+/// The scalar the chains sample paths by
+fn contribution(radiance: &Color) -> f64 {
+    let c = luminance(radiance);
+    if c.is_finite() {
+        c.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// The scene paths are traced in
+struct Scene<'a> {
+    camera: &'a Camera,
+    background: &'a Background,
+    world: &'a HittableEnum,
+    lights: &'a LightSampler,
+}
+
+impl Mlt {
+    /// `mutations_per_pixel` is the number of paths proposed per pixel of the image
+    pub fn new(tracer: PathTracer, mutations_per_pixel: usize) -> Self {
+        Self {
+            tracer,
+            mutations_per_pixel,
+            bootstrap_samples: 100_000,
+            chains: 1000,
+            sigma: 0.01,
+            large_step_probability: 0.3,
+        }
+    }
+
+    pub fn with_bootstrap_samples(mut self, bootstrap_samples: usize) -> Self {
+        self.bootstrap_samples = bootstrap_samples;
+        self
+    }
+
+    pub fn with_chains(mut self, chains: usize) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    pub fn with_sigma(mut self, sigma: f64) -> Self {
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn with_large_step_probability(mut self, large_step_probability: f64) -> Self {
+        self.large_step_probability = large_step_probability;
+        self
+    }
+
+    /// Renders a `width` by `height` image. The film holds a single sample per pixel.
+    pub fn render(
+        &self,
+        camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        width: usize,
+        height: usize,
+    ) -> Film {
+        let scene = Scene {
+            camera,
+            background,
+            world,
+            lights,
+        };
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+        let mut weights = vec![0.0; self.bootstrap_samples];
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|k| {
+                    let scene = &scene;
+                    scope.spawn(move || {
+                        (k..self.bootstrap_samples)
+                            .step_by(threads)
+                            .map(|i| (i, contribution(&self.trace(&self.samples(i), scene).2)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            for handle in handles {
+                for (i, weight) in handle.join().unwrap() {
+                    weights[i] = weight;
+                }
+            }
+        });
+        let mut film = Film::new(width, height);
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 || self.chains == 0 {
+            return film;
+        }
+        let cdf: Vec<f64> = weights
+            .iter()
+            .scan(0.0, |sum, weight| {
+                *sum += weight;
+                Some(*sum)
+            })
+            .collect();
+
+        // Each splat estimates the whole image, with the brightness of the bootstrap paths
+        let mutations = self.mutations_per_pixel * width * height;
+        let scale = total / self.bootstrap_samples as f64 / self.mutations_per_pixel as f64;
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|k| {
+                    let (scene, cdf) = (&scene, &cdf);
+                    scope.spawn(move || {
+                        let mut film = Film::new(width, height);
+                        for chain in (k..self.chains).step_by(threads) {
+                            let count = mutations / self.chains
+                                + usize::from(chain < mutations % self.chains);
+                            self.run_chain(scene, cdf, count, scale, &mut film);
+                        }
+                        film
+                    })
+                })
+                .collect();
+            for handle in handles {
+                film.merge(&handle.join().unwrap());
+            }
+        });
+        film
+    }
+
+    /// Samples that give the `index`th bootstrap path first
+    fn samples(&self, index: usize) -> Rc<RefCell<PrimarySamples>> {
+        Rc::new(RefCell::new(PrimarySamples::new(
+            index as u64,
+            self.sigma,
+            self.large_step_probability,
+        )))
+    }
+
+    /// Traces the path `samples` stand for: where it lands on the film, and the light
+    /// it carries
+    fn trace(&self, samples: &Rc<RefCell<PrimarySamples>>, scene: &Scene) -> (f64, f64, Color) {
+        random::with_source(SharedSamples(Rc::clone(samples)), || {
+            let mut rng = random::rng();
+            let s = rng.gen_range(0.0..1.0);
+            let t = rng.gen_range(0.0..1.0);
+            let r = scene.camera.get_ray(s, t);
+            let (radiance, _) = self.tracer.color(
+                &r,
+                scene.camera,
+                scene.background,
+                scene.world,
+                scene.lights,
+                &mut Vec::new(),
+            );
+            (s, t, radiance)
+        })
+    }
+
+    /// Runs a chain from a bootstrap path picked by its contribution
+    fn run_chain(&self, scene: &Scene, cdf: &[f64], mutations: usize, scale: f64, film: &mut Film) {
+        let mut rng = rand::thread_rng();
+        let total = cdf[cdf.len() - 1];
+        let u = rng.gen_range(0.0..total);
+        let index = cdf.partition_point(|&c| c <= u).min(cdf.len() - 1);
+        let samples = self.samples(index);
+        let (mut s, mut t, mut radiance) = self.trace(&samples, scene);
+        let mut c = contribution(&radiance);
+
+        for _ in 0..mutations {
+            samples.borrow_mut().start_iteration();
+            let (proposed_s, proposed_t, proposed) = self.trace(&samples, scene);
+            let proposed_c = contribution(&proposed);
+            let accept = if c > 0.0 {
+                (proposed_c / c).min(1.0)
+            } else {
+                1.0
+            };
+
+            if accept > 0.0 && proposed_c > 0.0 {
+                film.add_splat(&Splat {
+                    s: proposed_s,
+                    t: proposed_t,
+                    color: proposed * (accept * scale / proposed_c),
+                });
+            }
+            if c > 0.0 {
+                film.add_splat(&Splat {
+                    s,
+                    t,
+                    color: radiance * ((1.0 - accept) * scale / c),
+                });
+            }
+
+            if rng.gen_range(0.0..1.0) < accept {
+                (s, t, radiance, c) = (proposed_s, proposed_t, proposed, proposed_c);
+                samples.borrow_mut().accept();
+            } else {
+                samples.borrow_mut().reject();
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::{Integrator, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::LightSampler;
+use crate::ray::Ray;
+use crate::vec3::Color;
+
+/// Shading normals at the first hit, facing the camera, mapped from [-1, 1] to [0, 1]
+#[derive(Debug, Clone)]
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        _background: &Background,
+        world: &HittableEnum,
+        _lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let mut rec = HitRecord::new();
+        if !world.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            return (Color::zero(), PathStats::first_hit(false));
+        }
+        let color = 0.5 * (rec.normal.unit() + Color::new(1.0, 1.0, 1.0));
+        (color, PathStats::first_hit(true))
+    }
+}
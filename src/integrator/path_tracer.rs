@@ -0,0 +1,74 @@
+use super::{Integrator, PathSettings, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::HittableEnum;
+use crate::light::LightSampler;
+use crate::random;
+use crate::ray::Ray;
+use crate::spectrum::{self, SampledWavelengths};
+use crate::vec3::Color;
+use rand::Rng;
+
+/// How a path tracer finds light
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightSampling {
+    /// Only by BSDF sampling
+    Bsdf,
+    /// With a shadow ray to a light at every non-specular bounce
+    Nee,
+    /// Light and BSDF sampling combined with the power heuristic
+    Mis,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathTracer {
+    sampling: LightSampling,
+    settings: PathSettings,
+    spectral: bool,
+}
+
+impl PathTracer {
+    pub fn new(sampling: LightSampling, settings: PathSettings) -> Self {
+        Self {
+            sampling,
+            settings,
+            spectral: false,
+        }
+    }
+
+    /// Traces four wavelengths per path instead of RGB
+    pub fn with_spectral(mut self, spectral: bool) -> Self {
+        self.spectral = spectral;
+        self
+    }
+}
+
+impl Integrator for PathTracer {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        if !self.spectral {
+            return r.color(self.sampling, background, world, lights, &self.settings);
+        }
+
+        let mut lambdas = SampledWavelengths::sample_uniform(random::rng().gen_range(0.0..1.0));
+        let mut r = r.clone();
+        r.wavelength = Some(lambdas.hero());
+        let (radiance, stats) = r.color_spectral(
+            self.sampling,
+            background,
+            world,
+            lights,
+            &mut lambdas,
+            &self.settings,
+        );
+        (spectrum::to_rgb(&radiance, &lambdas), stats)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Light arriving at a surface, carried there by a path from a light
+#[derive(Debug, Clone, Copy)]
+pub struct Photon {
+    pub p: Point3,
+    /// Unit vector towards where the light came from
+    pub direction: Vec3,
+    pub power: Color,
+}
+
+/// Balanced kd-tree over photons, kept in place: every range of the list is split at
+/// its median along `axes` at that index, the halves on either side
+#[derive(Debug, Clone)]
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    axes: Vec<usize>,
+}
+
+impl PhotonMap {
+    pub fn new(photons: Vec<Photon>) -> Self {
+        let mut map = Self {
+            axes: vec![0; photons.len()],
+            photons,
+        };
+        let len = map.photons.len();
+        map.build(0, len);
+        map
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// Splits along the axis where the photons spread most
+    fn build(&mut self, start: usize, end: usize) {
+        if end <= start {
+            return;
+        }
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = -min;
+        for photon in &self.photons[start..end] {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(photon.p[axis]);
+                max[axis] = max[axis].max(photon.p[axis]);
+            }
+        }
+        let extent = max - min;
+        let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        };
+
+        let mid = (start + end) / 2;
+        self.photons[start..end]
+            .select_nth_unstable_by(mid - start, |a, b| a.p[axis].total_cmp(&b.p[axis]));
+        self.axes[mid] = axis;
+        self.build(start, mid);
+        self.build(mid + 1, end);
+    }
+
+    /// Calls `f` with every photon within `radius` of `p`
+    pub fn for_each_within(&self, p: &Point3, radius: f64, mut f: impl FnMut(&Photon)) {
+        self.search(0, self.photons.len(), p, radius * radius, &mut f);
+    }
+
+    fn search(
+        &self,
+        start: usize,
+        end: usize,
+        p: &Point3,
+        radius_squared: f64,
+        f: &mut impl FnMut(&Photon),
+    ) {
+        if end <= start {
+            return;
+        }
+        let mid = (start + end) / 2;
+        let photon = &self.photons[mid];
+        if (photon.p - *p).power() <= radius_squared {
+            f(photon);
+        }
+
+        // The side of the split holding p first, the other only if the sphere reaches it
+        let d = p[self.axes[mid]] - photon.p[self.axes[mid]];
+        let (near, far) = if d <= 0.0 {
+            ((start, mid), (mid + 1, end))
+        } else {
+            ((mid + 1, end), (start, mid))
+        };
+        self.search(near.0, near.1, p, radius_squared, f);
+        if d * d <= radius_squared {
+            self.search(far.0, far.1, p, radius_squared, f);
+        }
+    }
+}
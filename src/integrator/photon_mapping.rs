@@ -0,0 +1,328 @@
+use super::{bounding_sphere, PathSettings, Photon, PhotonMap};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::{Light, LightEnum, LightSampler};
+use crate::material::{MaterialEnum, ScatterRecord};
+use crate::random;
+use crate::ray::Ray;
+use crate::vec3::{Color, Point3};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::thread;
+
+/// Stochastic progressive photon mapping (Hachisuka & Jensen). Every iteration follows a
+/// path from each pixel to the first surface that doesn't scatter like a mirror, traces
+/// photons from the lights and gathers the photons around that surface point, in a radius
+/// shrinking from one iteration to the next. Caustics, which paths from the camera can't
+/// find, converge along with the rest.
+///
+/// Photons are only left on surfaces: paths from either end go through media by sampling
+/// them. Renders the whole image at once rather than a sample at a time, see `render`.
+#[derive(Debug, Clone)]
+pub struct PhotonMapping {
+    settings: PathSettings,
+    iterations: usize,
+    photons_per_iteration: usize,
+    /// 1% of the radius of the scene's bounding sphere if None
+    initial_radius: Option<f64>,
+    /// Fraction of the photons gathered in an iteration kept by the shrinking radius
+    alpha: f64,
+}
+
+/// Where the camera path of a pixel stopped in the current iteration
+struct VisiblePoint {
+    ray: Ray,
+    rec: HitRecord,
+    material: MaterialEnum,
+    beta: Color,
+}
+
+struct Pixel {
+    /// Light found along camera paths, summed over iterations
+    direct: Color,
+    visible: Option<VisiblePoint>,
+    radius: f64,
+    /// Photons gathered so far and their flux, scaled down as the radius shrinks
+    photons: f64,
+    flux: Color,
+}
+
+fn max_component(c: &Color) -> f64 {
+    c.x().max(c.y()).max(c.z())
+}
+
+impl PhotonMapping {
+    pub fn new(settings: PathSettings, iterations: usize, photons_per_iteration: usize) -> Self {
+        Self {
+            settings,
+            iterations,
+            photons_per_iteration,
+            initial_radius: None,
+            alpha: 0.7,
+        }
+    }
+
+    pub fn with_initial_radius(mut self, initial_radius: f64) -> Self {
+        self.initial_radius = Some(initial_radius);
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Renders a `width` by `height` image. The film holds a single sample per pixel.
+    pub fn render(
+        &self,
+        camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        width: usize,
+        height: usize,
+    ) -> Film {
+        let (center, scene_radius) = bounding_sphere(world);
+        let radius = self.initial_radius.unwrap_or(scene_radius / 100.0);
+        let mut pixels: Vec<Pixel> = (0..width * height)
+            .map(|_| Pixel {
+                direct: Color::zero(),
+                visible: None,
+                radius,
+                photons: 0.0,
+                flux: Color::zero(),
+            })
+            .collect();
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = (pixels.len() / threads).max(1);
+
+        for _ in 0..self.iterations {
+            thread::scope(|scope| {
+                for (k, pixels) in pixels.chunks_mut(chunk).enumerate() {
+                    scope.spawn(move || {
+                        let mut rng = random::rng();
+                        for (index, pixel) in (k * chunk..).zip(pixels.iter_mut()) {
+                            let (i, j) = (index % width, index / width);
+                            let u = (i as f64 + rng.gen_range(0.0..1.0)) / width as f64;
+                            let v = (j as f64 + rng.gen_range(0.0..1.0)) / height as f64;
+                            self.trace_camera(camera.get_ray(u, v), background, world, pixel);
+                        }
+                    });
+                }
+            });
+
+            let photons: Vec<Photon> = thread::scope(|scope| {
+                let handles: Vec<_> = (0..threads)
+                    .map(|k| {
+                        let count = self.photons_per_iteration / threads
+                            + usize::from(k < self.photons_per_iteration % threads);
+                        scope.spawn(move || {
+                            let mut photons = Vec::new();
+                            for _ in 0..count {
+                                self.trace_photon(
+                                    world,
+                                    lights.lights(),
+                                    &center,
+                                    scene_radius,
+                                    &mut photons,
+                                );
+                            }
+                            photons
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| handle.join().unwrap())
+                    .collect()
+            });
+            let map = PhotonMap::new(photons);
+
+            thread::scope(|scope| {
+                for pixels in pixels.chunks_mut(chunk) {
+                    let map = &map;
+                    scope.spawn(move || {
+                        for pixel in pixels {
+                            self.gather(map, pixel);
+                        }
+                    });
+                }
+            });
+        }
+
+        let mut film = Film::new(width, height);
+        let iterations = self.iterations as f64;
+        let emitted = iterations * self.photons_per_iteration as f64;
+        for (index, pixel) in pixels.iter().enumerate() {
+            let area = PI * pixel.radius * pixel.radius;
+            let color = pixel.direct / iterations + pixel.flux / (emitted * area);
+            film.add_sample(index % width, index / width, &color);
+        }
+        film
+    }
+
+    /// Follows `r` through mirrors, glass and media, collecting the light it sees on the
+    /// way, and leaves the pixel's visible point where it stops
+    fn trace_camera(
+        &self,
+        r: Ray,
+        background: &Background,
+        world: &HittableEnum,
+        pixel: &mut Pixel,
+    ) {
+        let mut rng = random::rng();
+        let mut ray = r;
+        let mut beta = Color::new(1.0, 1.0, 1.0);
+        pixel.visible = None;
+
+        for bounce in 0..self.settings.max_depth {
+            let mut rec = HitRecord::new();
+            let hit = world.hit(&ray, 0.001, f64::INFINITY, &mut rec);
+            pixel.direct += beta * ray.glow(world, hit, &rec);
+            if !hit {
+                pixel.direct += beta * background.value(&ray.direction);
+                return;
+            }
+            beta = beta * ray.through_interior(&mut rec);
+            let material = match rec.material.clone() {
+                Some(material) => material,
+                None => panic!("Material is None"),
+            };
+            if material.is_emissive() {
+                pixel.direct += beta
+                    * material.emitted(rec.u, rec.v, &rec.p)
+                    * material.emission_falloff(&rec, &-ray.direction);
+            }
+
+            let mut srec = ScatterRecord::new();
+            if !material.sample(&ray, &rec, &mut srec) {
+                return;
+            }
+            if !srec.is_delta && !matches!(material, MaterialEnum::Isotropic(_)) {
+                pixel.visible = Some(VisiblePoint {
+                    ray,
+                    rec,
+                    material,
+                    beta,
+                });
+                return;
+            }
+            beta = beta * srec.attenuation;
+
+            if bounce + 1 >= self.settings.rr_depth {
+                let survival = max_component(&beta).min(0.95);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    return;
+                }
+                beta /= survival;
+            }
+            ray = srec.scattered;
+        }
+    }
+
+    /// Traces a photon from a light picked uniformly, leaving it on every surface that
+    /// doesn't scatter like a mirror
+    fn trace_photon(
+        &self,
+        world: &HittableEnum,
+        lights: &[LightEnum],
+        center: &Point3,
+        radius: f64,
+        photons: &mut Vec<Photon>,
+    ) {
+        if lights.is_empty() {
+            return;
+        }
+        let mut rng = random::rng();
+        let light = &lights[rng.gen_range(0..lights.len())];
+        let emission = match light.sample_emission(center, radius) {
+            Some(e) if e.pdf_position > 0.0 && e.pdf_direction > 0.0 => e,
+            _ => return,
+        };
+        let cos_theta = if emission.normal == Point3::zero() {
+            1.0
+        } else {
+            emission.normal.dot(&emission.direction).abs()
+        };
+        let mut beta = emission.radiance * cos_theta * lights.len() as f64
+            / (emission.pdf_position * emission.pdf_direction);
+        let reference = max_component(&beta);
+        if reference <= 0.0 {
+            return;
+        }
+        // The scenes open the shutter from 0 to 1
+        let mut ray = Ray::new(
+            &emission.origin,
+            &emission.direction,
+            rng.gen_range(0.0..1.0),
+        );
+
+        for bounce in 0..self.settings.max_depth {
+            let mut rec = HitRecord::new();
+            if !world.hit(&ray, 0.001, f64::INFINITY, &mut rec) {
+                return;
+            }
+            beta = beta * ray.through_interior(&mut rec);
+            let material = match rec.material.clone() {
+                Some(material) => material,
+                None => panic!("Material is None"),
+            };
+
+            let mut srec = ScatterRecord::new();
+            if !material.sample(&ray, &rec, &mut srec) {
+                return;
+            }
+            if !srec.is_delta && !matches!(material, MaterialEnum::Isotropic(_)) {
+                photons.push(Photon {
+                    p: rec.p,
+                    direction: -ray.direction.unit(),
+                    power: beta,
+                });
+            }
+            beta = beta * srec.attenuation;
+
+            if bounce + 1 >= self.settings.rr_depth {
+                let survival = (max_component(&beta) / reference).min(0.95);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    return;
+                }
+                beta /= survival;
+            }
+            ray = srec.scattered;
+        }
+    }
+
+    /// Adds the photons around the pixel's visible point, and shrinks its radius so that
+    /// only a fraction `alpha` of them count towards the photon density
+    fn gather(&self, map: &PhotonMap, pixel: &mut Pixel) {
+        let visible = match &pixel.visible {
+            Some(visible) => visible,
+            None => return,
+        };
+        let mut count = 0usize;
+        let mut flux = Color::zero();
+        map.for_each_within(&visible.rec.p, pixel.radius, |photon| {
+            count += 1;
+            let cos_theta = visible.rec.normal.dot(&photon.direction).abs();
+            if cos_theta > 0.0 {
+                let f = visible
+                    .material
+                    .eval(&visible.ray, &visible.rec, &photon.direction);
+                flux += f / cos_theta * photon.power;
+            }
+        });
+        if count == 0 {
+            return;
+        }
+
+        let count = count as f64;
+        let photons = pixel.photons + self.alpha * count;
+        let radius = pixel.radius * (photons / (pixel.photons + count)).sqrt();
+        let shrink = (radius / pixel.radius).powi(2);
+        pixel.flux = (pixel.flux + visible.beta * flux) * shrink;
+        pixel.photons = photons;
+        pixel.radius = radius;
+    }
+}
@@ -0,0 +1,30 @@
+use super::{Integrator, PathStats};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::LightSampler;
+use crate::ray::Ray;
+use crate::vec3::Color;
+
+/// Texture coordinates at the first hit, u in red and v in green
+#[derive(Debug, Clone)]
+pub struct Uv;
+
+impl Integrator for Uv {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        _background: &Background,
+        world: &HittableEnum,
+        _lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let mut rec = HitRecord::new();
+        if !world.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            return (Color::zero(), PathStats::first_hit(false));
+        }
+        (Color::new(rec.u, rec.v, 0.0), PathStats::first_hit(true))
+    }
+}
@@ -0,0 +1,227 @@
+use super::{power_heuristic, Integrator, PathSettings, PathStats, Termination};
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::film::Splat;
+use crate::hittable::{HitRecord, Hittable, HittableEnum};
+use crate::light::{Light, LightSampler};
+use crate::material::{MaterialEnum, ScatterRecord};
+use crate::random;
+use crate::ray::{light_normal_at, Ray};
+use crate::vec3::{Color, Vec3};
+use rand::Rng;
+
+/// Path tracing for participating media. Lights are sampled from media as from surfaces,
+/// with shadow rays that take the transmittance of the media they cross instead of
+/// stopping at them, and combined with BSDF sampling as `LightSampling::Mis` does.
+///
+/// Point and spot lights are found from media by equiangular sampling along every ray
+/// (Kulla & Fajardo), which puts points where the ray passes close to the light, rather
+/// than from the points where the ray scatters.
+#[derive(Debug, Clone)]
+pub struct VolumePathTracer {
+    settings: PathSettings,
+}
+
+impl VolumePathTracer {
+    pub fn new(settings: PathSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Light from a light picked for `rec`, reflected or scattered back along `ray`
+    fn sample_light(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        material: &MaterialEnum,
+        world: &HittableEnum,
+        lights: &LightSampler,
+    ) -> Color {
+        let in_medium = matches!(material, MaterialEnum::Isotropic(_));
+        let normal = light_normal_at(rec, material);
+        let (light, pmf) = match lights.sample(&rec.p, &normal) {
+            Some(choice) => choice,
+            None => return Color::zero(),
+        };
+        // Equiangular sampling finds point and spot lights from media
+        if in_medium && light.position().is_some() {
+            return Color::zero();
+        }
+        let sample = match light.sample(&rec.p) {
+            Some(sample) if sample.pdf > 0.0 => sample,
+            _ => return Color::zero(),
+        };
+        let f = material.eval(ray, rec, &sample.direction);
+        if f == Color::zero() {
+            return Color::zero();
+        }
+
+        let mut shadow = ray.spawn(&rec.p, &sample.direction);
+        shadow.interior = ray.interior_towards(rec, material, &sample.direction);
+        let visibility = world.transmittance(&shadow, 0.001, sample.distance * (1.0 - 1e-6));
+        if visibility == 0.0 {
+            return Color::zero();
+        }
+        let weight = if light.is_delta() {
+            1.0
+        } else {
+            power_heuristic(
+                lights.pdf(&rec.p, &normal, &sample.direction),
+                material.pdf(ray, rec, &sample.direction),
+            )
+        };
+        shadow.interior.transmittance(sample.distance.min(f64::MAX))
+            * f
+            * sample.radiance
+            * (visibility * weight / (pmf * sample.pdf))
+    }
+
+    /// Light from a point or spot light scattered back along `ray` by the media it goes
+    /// through before `t_max`. The transmittance along `ray` is left out, as the chance
+    /// that distance sampling let the ray get that far accounts for it.
+    fn equiangular(
+        &self,
+        ray: &Ray,
+        t_max: f64,
+        world: &HittableEnum,
+        lights: &LightSampler,
+    ) -> Color {
+        let positional = || lights.lights().iter().filter(|l| l.position().is_some());
+        let count = positional().count();
+        if count == 0 {
+            return Color::zero();
+        }
+        let mut segments = Vec::new();
+        world.media(ray, 0.001, t_max, &mut segments);
+        if segments.is_empty() {
+            return Color::zero();
+        }
+
+        let mut rng = random::rng();
+        let light = positional().nth(rng.gen_range(0..count)).unwrap();
+        let position = light.position().unwrap();
+        let length = ray.direction.len();
+        let direction = ray.direction / length;
+        // Distance along the ray to the point closest to the light, and from there to it
+        let delta = (position - ray.origin).dot(&direction);
+        let h = (position - (ray.origin + direction * delta)).len();
+        if h == 0.0 {
+            return Color::zero();
+        }
+
+        let mut radiance = Color::zero();
+        for segment in &segments {
+            let theta_a = ((segment.t0 * length - delta) / h).atan();
+            let theta_b = ((segment.t1 * length - delta) / h).atan();
+            let x = h * (theta_a + rng.gen_range(0.0..1.0) * (theta_b - theta_a)).tan();
+            let pdf = h / ((theta_b - theta_a) * (h * h + x * x));
+            if !(pdf > 0.0 && pdf.is_finite()) {
+                continue;
+            }
+            let distance = delta + x;
+
+            let mut rec = HitRecord::new();
+            rec.p = ray.origin + direction * distance;
+            let sample = match light.sample(&rec.p) {
+                Some(sample) if sample.pdf > 0.0 => sample,
+                _ => continue,
+            };
+            let shadow = ray.spawn(&rec.p, &sample.direction);
+            let visibility = world.transmittance(&shadow, 0.001, sample.distance * (1.0 - 1e-6));
+            if visibility == 0.0 {
+                continue;
+            }
+            let phase = segment.phase_function.eval(ray, &rec, &sample.direction);
+            radiance += ray.interior.transmittance(distance)
+                * shadow.interior.transmittance(sample.distance)
+                * phase
+                * sample.radiance
+                * (segment.density * visibility * count as f64 / (sample.pdf * pdf));
+        }
+        radiance
+    }
+}
+
+impl Integrator for VolumePathTracer {
+    fn color(
+        &self,
+        r: &Ray,
+        _camera: &Camera,
+        background: &Background,
+        world: &HittableEnum,
+        lights: &LightSampler,
+        _splats: &mut Vec<Splat>,
+    ) -> (Color, PathStats) {
+        let mut rng = random::rng();
+        let mut radiance = Color::zero();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = r.clone();
+        // Density of the last BSDF sample, None after a specular bounce or for camera rays
+        let mut bsdf_pdf: Option<f64> = None;
+        // Normal the lights were picked for at the origin of `ray`
+        let mut light_normal = Vec3::zero();
+
+        for bounce in 0..self.settings.max_depth {
+            let mut rec = HitRecord::new();
+            let hit = world.hit(&ray, 0.001, f64::INFINITY, &mut rec);
+            let t_max = if hit { rec.t } else { f64::INFINITY };
+            radiance += throughput * self.equiangular(&ray, t_max, world, lights);
+            radiance += throughput * ray.glow(world, hit, &rec);
+
+            let weight = match bsdf_pdf {
+                Some(pdf) => {
+                    power_heuristic(pdf, lights.pdf(&ray.origin, &light_normal, &ray.direction))
+                }
+                None => 1.0,
+            };
+            if !hit {
+                radiance += throughput * background.value(&ray.direction) * weight;
+                return (radiance, PathStats::new(bounce, Termination::Escaped));
+            }
+
+            throughput = throughput * ray.through_interior(&mut rec);
+            let material = match rec.material.clone() {
+                Some(material) => material,
+                None => panic!("Material is None"),
+            };
+            if material.is_emissive() {
+                radiance += throughput
+                    * material.emitted(rec.u, rec.v, &rec.p)
+                    * material.emission_falloff(&rec, &-ray.direction)
+                    * weight;
+            }
+
+            let mut srec = ScatterRecord::new();
+            if !material.sample(&ray, &rec, &mut srec) {
+                return (radiance, PathStats::new(bounce + 1, Termination::Absorbed));
+            }
+            bsdf_pdf = None;
+            if !srec.is_delta && !lights.is_empty() {
+                radiance += throughput * self.sample_light(&ray, &rec, &material, world, lights);
+                bsdf_pdf = Some(srec.pdf);
+                light_normal = light_normal_at(&rec, &material);
+            }
+            throughput = throughput * srec.attenuation;
+
+            if bounce + 1 >= self.settings.rr_depth {
+                let survival = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(0.95);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    return (
+                        radiance,
+                        PathStats::new(bounce + 1, Termination::RussianRoulette),
+                    );
+                }
+                throughput /= survival;
+            }
+            ray = srec.scattered;
+        }
+
+        (
+            radiance,
+            PathStats::new(self.settings.max_depth, Termination::MaxDepth),
+        )
+    }
+}
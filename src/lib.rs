@@ -1,9 +1,16 @@
+pub mod background;
 pub mod camera;
+pub mod film;
 pub mod hittable;
+pub mod integrator;
+pub mod light;
 pub mod material;
+pub mod onb;
 pub mod progress;
+pub mod random;
 pub mod ray;
 pub mod scenes;
+pub mod spectrum;
 pub mod texture;
 pub mod vec3;
 
@@ -0,0 +1,277 @@
+use crate::background::{Background, EnvironmentMap, Sky};
+use crate::hittable::{HitRecord, Hittable, HittableEnum, AABB};
+use crate::onb::Onb;
+use crate::random;
+use crate::ray::Ray;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+pub mod directional;
+pub use directional::DirectionalLight;
+pub mod ies;
+pub use ies::IesProfile;
+pub mod light_bvh;
+pub use light_bvh::{LightBounds, LightBvh};
+pub mod point;
+pub use point::PointLight;
+pub mod sampler;
+pub use sampler::LightSampler;
+pub mod spot;
+pub use spot::SpotLight;
+
+/// Light arriving at a point from a sampled direction
+pub struct LightSample {
+    /// Unit vector towards the light
+    pub direction: Vec3,
+    /// Distance to the sampled point, infinite for lights at infinity
+    pub distance: f64,
+    /// Radiance arriving from the light, before any occlusion
+    pub radiance: Color,
+    /// Solid angle density of `direction`
+    pub pdf: f64,
+}
+
+/// Light leaving a light, for paths traced from the lights
+pub struct EmissionSample {
+    pub origin: Point3,
+    /// Normal of the emitting surface, zero for points
+    pub normal: Vec3,
+    /// Unit vector the light travels along
+    pub direction: Vec3,
+    pub radiance: Color,
+    /// Area density of `origin`, 1 for points
+    pub pdf_position: f64,
+    /// Solid angle density of `direction`, 1 for a single direction
+    pub pdf_direction: f64,
+}
+
+pub trait Light {
+    /// Samples a direction from `origin` towards the light
+    fn sample(&self, origin: &Point3) -> Option<LightSample>;
+    /// Solid angle density with which `sample` picks `direction` from `origin`
+    fn pdf(&self, origin: &Point3, direction: &Vec3) -> f64;
+    /// Samples light leaving the light. Lights at infinity send it through a disk
+    /// facing the scene, which must lie within the sphere at `center` of `radius`.
+    fn sample_emission(&self, center: &Point3, radius: f64) -> Option<EmissionSample>;
+    /// Solid angle density with which `sample_emission` sends light along `direction`
+    /// from `origin`, where the light has the normal `normal`
+    fn pdf_emission(&self, origin: &Point3, normal: &Vec3, direction: &Vec3) -> f64;
+    /// Whether rays can't hit the light, so that only its own samples find it and
+    /// `pdf` is 0 everywhere
+    fn is_delta(&self) -> bool {
+        false
+    }
+    /// Where the light is and how much it emits, None for lights at infinity
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum LightEnum {
+    /// An emissive primitive of the scene
+    Area(HittableEnum),
+    Environment(EnvironmentMap),
+    /// The sun disk of a `Sky`
+    Sun(Sky),
+    Point(PointLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
+}
+
+impl Light for LightEnum {
+    fn sample(&self, origin: &Point3) -> Option<LightSample> {
+        match self {
+            LightEnum::Area(a) => sample_area(a, origin),
+            LightEnum::Environment(e) => e.sample(origin),
+            LightEnum::Sun(s) => s.sample(origin),
+            LightEnum::Point(p) => p.sample(origin),
+            LightEnum::Spot(s) => s.sample(origin),
+            LightEnum::Directional(d) => d.sample(origin),
+        }
+    }
+
+    fn pdf(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        match self {
+            LightEnum::Area(a) => a.pdf_value(origin, direction),
+            LightEnum::Environment(e) => e.pdf(origin, direction),
+            LightEnum::Sun(s) => s.pdf(origin, direction),
+            LightEnum::Point(p) => p.pdf(origin, direction),
+            LightEnum::Spot(s) => s.pdf(origin, direction),
+            LightEnum::Directional(d) => d.pdf(origin, direction),
+        }
+    }
+
+    fn sample_emission(&self, center: &Point3, radius: f64) -> Option<EmissionSample> {
+        match self {
+            LightEnum::Area(a) => sample_area_emission(a),
+            LightEnum::Environment(e) => e.sample_emission(center, radius),
+            LightEnum::Sun(s) => s.sample_emission(center, radius),
+            LightEnum::Point(p) => p.sample_emission(center, radius),
+            LightEnum::Spot(s) => s.sample_emission(center, radius),
+            LightEnum::Directional(d) => d.sample_emission(center, radius),
+        }
+    }
+
+    fn pdf_emission(&self, origin: &Point3, normal: &Vec3, direction: &Vec3) -> f64 {
+        match self {
+            LightEnum::Area(_) => area_pdf_emission(normal, direction),
+            LightEnum::Environment(e) => e.pdf_emission(origin, normal, direction),
+            LightEnum::Sun(s) => s.pdf_emission(origin, normal, direction),
+            LightEnum::Point(p) => p.pdf_emission(origin, normal, direction),
+            LightEnum::Spot(s) => s.pdf_emission(origin, normal, direction),
+            LightEnum::Directional(d) => d.pdf_emission(origin, normal, direction),
+        }
+    }
+
+    fn is_delta(&self) -> bool {
+        match self {
+            LightEnum::Area(_) | LightEnum::Environment(_) | LightEnum::Sun(_) => false,
+            LightEnum::Point(p) => p.is_delta(),
+            LightEnum::Spot(s) => s.is_delta(),
+            LightEnum::Directional(d) => d.is_delta(),
+        }
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        match self {
+            LightEnum::Area(a) => {
+                let mut bbox = AABB::new(&Point3::zero(), &Point3::zero());
+                if !a.bounding_box(0.0, 1.0, &mut bbox) {
+                    return None;
+                }
+                // Lights estimated to be black may still shine somewhere, so keep a
+                // chance of picking them
+                Some(LightBounds::omnidirectional(bbox, a.power().max(1e-6)))
+            }
+            LightEnum::Environment(_) | LightEnum::Sun(_) | LightEnum::Directional(_) => None,
+            LightEnum::Point(p) => p.bounds(),
+            LightEnum::Spot(s) => s.bounds(),
+        }
+    }
+}
+
+impl LightEnum {
+    /// Whether the light is infinitely far away, so that its samples have an infinite
+    /// distance and its emission comes from outside the scene
+    pub fn is_infinite(&self) -> bool {
+        matches!(
+            self,
+            LightEnum::Environment(_) | LightEnum::Sun(_) | LightEnum::Directional(_)
+        )
+    }
+
+    /// Center of point and spot lights
+    pub fn position(&self) -> Option<Point3> {
+        match self {
+            LightEnum::Point(p) => Some(p.position()),
+            LightEnum::Spot(s) => Some(s.position()),
+            _ => None,
+        }
+    }
+}
+
+fn sample_area(area: &HittableEnum, origin: &Point3) -> Option<LightSample> {
+    let direction = area.random(origin).unit();
+    let pdf = area.pdf_value(origin, &direction);
+    if pdf <= 0.0 {
+        return None;
+    }
+    let mut rec = HitRecord::new();
+    if !area.hit(
+        &Ray::new(origin, &direction, 0.0),
+        0.001,
+        f64::INFINITY,
+        &mut rec,
+    ) {
+        return None;
+    }
+    let material = rec.material.as_ref()?;
+    let radiance =
+        material.emitted(rec.u, rec.v, &rec.p) * material.emission_falloff(&rec, &-direction);
+    Some(LightSample {
+        direction,
+        distance: rec.t,
+        radiance,
+        pdf,
+    })
+}
+
+/// Both sides of an emissive primitive emit, cosine distributed
+fn sample_area_emission(area: &HittableEnum) -> Option<EmissionSample> {
+    let surface_area = area.area();
+    if surface_area <= 0.0 {
+        return None;
+    }
+    let (point, outward) = area.sample_surface();
+    let normal = if random::rng().gen_range(0.0..1.0) < 0.5 {
+        outward
+    } else {
+        -outward
+    };
+    let direction = normal + Vec3::rand_unit_vector();
+    if direction.power() < 1e-12 {
+        return None;
+    }
+    let direction = direction.unit();
+
+    // Hit the point from the emitting side for its texture coordinates
+    let epsilon = 1e-4 * (1.0 + point.len());
+    let mut rec = HitRecord::new();
+    if !area.hit(
+        &Ray::new(&(point + epsilon * normal), &-normal, 0.0),
+        0.0,
+        2.0 * epsilon,
+        &mut rec,
+    ) {
+        return None;
+    }
+    let material = rec.material.as_ref()?;
+    let radiance =
+        material.emitted(rec.u, rec.v, &rec.p) * material.emission_falloff(&rec, &direction);
+    Some(EmissionSample {
+        origin: rec.p,
+        normal,
+        direction,
+        radiance,
+        pdf_position: 1.0 / surface_area,
+        pdf_direction: area_pdf_emission(&normal, &direction),
+    })
+}
+
+fn area_pdf_emission(normal: &Vec3, direction: &Vec3) -> f64 {
+    normal.dot(&direction.unit()).abs() / (2.0 * PI)
+}
+
+/// Emission of a light at infinity from its sample `towards`, taken anywhere: the light
+/// enters the sphere at `center` of `radius` through the disk facing it
+pub(crate) fn emission_from_infinity(
+    towards: &LightSample,
+    center: &Point3,
+    radius: f64,
+) -> EmissionSample {
+    let uvw = Onb::build_from_w(&towards.direction);
+    let disk = Vec3::rand_in_unit_disk();
+    EmissionSample {
+        origin: *center + radius * (towards.direction + disk.x() * uvw.u + disk.y() * uvw.v),
+        normal: -towards.direction,
+        direction: -towards.direction,
+        radiance: towards.radiance,
+        pdf_position: 1.0 / (PI * radius * radius),
+        pdf_direction: towards.pdf,
+    }
+}
+
+/// The lights to sample in a scene: its emissive primitives, and the background
+/// if it can be importance sampled
+pub fn collect_lights(world: &HittableEnum, background: &Background) -> Vec<LightEnum> {
+    let mut areas = Vec::new();
+    world.lights(&mut areas);
+    let mut lights: Vec<LightEnum> = areas.into_iter().map(LightEnum::Area).collect();
+    if let Some(light) = background.light() {
+        lights.push(light);
+    }
+    lights
+}
@@ -0,0 +1,81 @@
+use super::{emission_from_infinity, EmissionSample, Light, LightSample};
+use crate::onb::Onb;
+use crate::random;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Parallel light from infinitely far away, like the sun. With an angular diameter
+/// it comes from a small disk in the sky instead, casting soft shadows.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirectionalLight {
+    /// Direction the light travels in
+    direction: Vec3,
+    /// Irradiance on a surface facing the light
+    irradiance: Color,
+    cos_radius: f64,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: &Vec3, irradiance: &Color) -> Self {
+        Self {
+            direction: direction.unit(),
+            irradiance: *irradiance,
+            cos_radius: 1.0,
+        }
+    }
+
+    pub fn with_angular_diameter(mut self, degrees: f64) -> Self {
+        self.cos_radius = crate::degrees_to_radians(degrees / 2.0).cos();
+        self
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _origin: &Point3) -> Option<LightSample> {
+        if self.cos_radius >= 1.0 {
+            return Some(LightSample {
+                direction: -self.direction,
+                distance: f64::INFINITY,
+                radiance: self.irradiance,
+                pdf: 1.0,
+            });
+        }
+
+        // Uniform over the disk, whose radiance adds up to the irradiance
+        let mut rng = random::rng();
+        let solid_angle = 2.0 * PI * (1.0 - self.cos_radius);
+        let cos_theta = 1.0 + rng.gen_range(0.0..1.0) * (self.cos_radius - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+        let direction = Onb::build_from_w(&-self.direction).local(&Vec3::new(
+            phi.cos() * sin_theta,
+            phi.sin() * sin_theta,
+            cos_theta,
+        ));
+        Some(LightSample {
+            direction,
+            distance: f64::INFINITY,
+            radiance: self.irradiance / solid_angle,
+            pdf: 1.0 / solid_angle,
+        })
+    }
+
+    fn pdf(&self, _origin: &Point3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    fn sample_emission(&self, center: &Point3, radius: f64) -> Option<EmissionSample> {
+        let towards = self.sample(center)?;
+        Some(emission_from_infinity(&towards, center, radius))
+    }
+
+    fn pdf_emission(&self, _origin: &Point3, _normal: &Vec3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,174 @@
+use crate::onb::Onb;
+use crate::vec3::Vec3;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Angular distribution of a light fixture, read from an IES LM-63 photometric file
+/// (type C photometry). Vertical angles are measured from the nadir, the axis along
+/// which the fixture points, and horizontal angles around it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IesProfile {
+    /// Increasing, in degrees
+    vertical_angles: Vec<f64>,
+    /// Increasing, in degrees
+    horizontal_angles: Vec<f64>,
+    /// Candela values, all vertical angles for each horizontal angle in turn
+    candela: Vec<f64>,
+    max_candela: f64,
+}
+
+fn parse_numbers(text: &str) -> Result<Vec<f64>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<f64>()
+                .map_err(|_| format!("Invalid number in IES file: {}", s))
+        })
+        .collect()
+}
+
+/// Index `i` such that `angles[i] <= angle <= angles[i + 1]`, and the position of
+/// `angle` between the two
+fn locate(angles: &[f64], angle: f64) -> (usize, f64) {
+    if angles.len() == 1 {
+        return (0, 0.0);
+    }
+    let i = angles
+        .partition_point(|&a| a <= angle)
+        .clamp(1, angles.len() - 1)
+        - 1;
+    let span = angles[i + 1] - angles[i];
+    let t = if span > 0.0 {
+        ((angle - angles[i]) / span).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (i, t)
+}
+
+impl FromStr for IesProfile {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let tilt = text
+            .find("TILT=")
+            .ok_or_else(|| "Missing TILT line in IES file".to_string())?;
+        let after_tilt = &text[tilt..];
+        let line_end = after_tilt.find('\n').unwrap_or(after_tilt.len());
+        let mut numbers = parse_numbers(&after_tilt[line_end..])?.into_iter();
+        let mut next = || {
+            numbers
+                .next()
+                .ok_or_else(|| "Unexpected end of IES file".to_string())
+        };
+
+        if after_tilt[..line_end].trim() == "TILT=INCLUDE" {
+            // Lamp to luminaire geometry, then tilt angles and factors, ignored
+            next()?;
+            let pairs = next()? as usize;
+            for _ in 0..2 * pairs {
+                next()?;
+            }
+        }
+
+        let _lamps = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        if photometric_type != 1.0 {
+            return Err("Only type C photometry is supported".to_string());
+        }
+        // Units, dimensions of the luminous opening, ballast factors and input watts
+        for _ in 0..7 {
+            next()?;
+        }
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err("IES file without angles".to_string());
+        }
+
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let candela = (0..vertical_count * horizontal_count)
+            .map(|_| next().map(|c| c * multiplier))
+            .collect::<Result<Vec<_>, _>>()?;
+        let max_candela = candela.iter().cloned().fold(0.0, f64::max);
+        if max_candela <= 0.0 {
+            return Err("IES file without any light".to_string());
+        }
+
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            max_candela,
+        })
+    }
+}
+
+impl IesProfile {
+    pub fn new(filename: &str) -> Self {
+        let text = std::fs::read_to_string(filename);
+        if text.is_err() {
+            panic!("Error: Failed to open IES file: {}", filename);
+        }
+        match text.unwrap().parse() {
+            Ok(profile) => profile,
+            Err(e) => panic!("Error: Failed to parse {}: {}", filename, e),
+        }
+    }
+
+    fn candela_at(&self, h: usize, v: usize) -> f64 {
+        self.candela[h * self.vertical_angles.len() + v]
+    }
+
+    /// Intensity towards `vertical` and `horizontal` degrees relative to the brightest
+    /// direction of the fixture
+    pub fn value(&self, vertical: f64, horizontal: f64) -> f64 {
+        let first = self.vertical_angles[0];
+        let last = self.vertical_angles[self.vertical_angles.len() - 1];
+        if vertical < first || vertical > last {
+            return 0.0;
+        }
+
+        // Fold the horizontal angle into the range covered, by the symmetry that implies
+        let horizontal = horizontal.rem_euclid(360.0);
+        let horizontal = match self.horizontal_angles[self.horizontal_angles.len() - 1] {
+            h if h <= 0.0 => 0.0,
+            h if h <= 90.0 => {
+                let folded = horizontal % 180.0;
+                folded.min(180.0 - folded)
+            }
+            h if h <= 180.0 => horizontal.min(360.0 - horizontal),
+            _ => horizontal,
+        };
+
+        let (v, tv) = locate(&self.vertical_angles, vertical);
+        let (h, th) = locate(&self.horizontal_angles, horizontal);
+        let v1 = (v + 1).min(self.vertical_angles.len() - 1);
+        let h1 = (h + 1).min(self.horizontal_angles.len() - 1);
+        let lerp = |h| (1.0 - tv) * self.candela_at(h, v) + tv * self.candela_at(h, v1);
+        ((1.0 - th) * lerp(h) + th * lerp(h1)) / self.max_candela
+    }
+
+    /// `value` towards `direction`, for a fixture pointing along `axis` with horizontal
+    /// angle 0 on the side of `reference`
+    pub fn value_towards(&self, axis: &Vec3, reference: &Vec3, direction: &Vec3) -> f64 {
+        let w = axis.unit();
+        let tangent = *reference - reference.dot(&w) * w;
+        let u = if tangent.power() > 1e-12 {
+            tangent.unit()
+        } else {
+            Onb::build_from_w(&w).u
+        };
+        let d = direction.unit();
+        let vertical = d.dot(&w).clamp(-1.0, 1.0).acos().to_degrees();
+        let horizontal = d.dot(&w.cross(&u)).atan2(d.dot(&u)).to_degrees();
+        self.value(vertical, horizontal)
+    }
+}
@@ -0,0 +1,323 @@
+use super::{Light, LightEnum};
+use crate::hittable::{surrounding_box, HitRecord, Hittable, AABB};
+use crate::random;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Where a light is, how much it emits and in which directions: it emits `phi` from
+/// within `bounds`, along directions at most `acos(cos_theta_o)` from `w` plus a
+/// spread of `acos(cos_theta_e)` around them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LightBounds {
+    pub bounds: AABB,
+    pub phi: f64,
+    pub w: Vec3,
+    pub cos_theta_o: f64,
+    pub cos_theta_e: f64,
+}
+
+/// cos(max(0, a - b)) from the sines and cosines of `a` and `b`
+fn cos_sub_clamped(sin_a: f64, cos_a: f64, sin_b: f64, cos_b: f64) -> f64 {
+    if cos_a > cos_b {
+        return 1.0;
+    }
+    cos_a * cos_b + sin_a * sin_b
+}
+
+/// sin(max(0, a - b)) from the sines and cosines of `a` and `b`
+fn sin_sub_clamped(sin_a: f64, cos_a: f64, sin_b: f64, cos_b: f64) -> f64 {
+    if cos_a > cos_b {
+        return 0.0;
+    }
+    sin_a * cos_b - cos_a * sin_b
+}
+
+fn sin_from_cos(cos: f64) -> f64 {
+    (1.0 - cos * cos).max(0.0).sqrt()
+}
+
+/// Rotates `v` by `theta` around the unit `axis`
+fn rotate(v: &Vec3, axis: &Vec3, theta: f64) -> Vec3 {
+    let (sin, cos) = theta.sin_cos();
+    *v * cos + axis.cross(v) * sin + *axis * axis.dot(v) * (1.0 - cos)
+}
+
+impl LightBounds {
+    /// Bounds of a light emitting in every direction
+    pub fn omnidirectional(bounds: AABB, phi: f64) -> Self {
+        Self {
+            bounds,
+            phi,
+            w: Vec3::new(0.0, 0.0, 1.0),
+            cos_theta_o: -1.0,
+            cos_theta_e: (PI / 2.0).cos(),
+        }
+    }
+
+    fn centroid(&self) -> Point3 {
+        (self.bounds.min() + self.bounds.max()) / 2.0
+    }
+
+    fn union(&self, other: &LightBounds) -> LightBounds {
+        let (w, cos_theta_o) = self.union_cone(other);
+        LightBounds {
+            bounds: surrounding_box(&self.bounds, &other.bounds),
+            phi: self.phi + other.phi,
+            w,
+            cos_theta_o,
+            cos_theta_e: self.cos_theta_e.min(other.cos_theta_e),
+        }
+    }
+
+    /// Smallest cone around both emission cones
+    fn union_cone(&self, other: &LightBounds) -> (Vec3, f64) {
+        let theta_a = self.cos_theta_o.clamp(-1.0, 1.0).acos();
+        let theta_b = other.cos_theta_o.clamp(-1.0, 1.0).acos();
+        let theta_d = self.w.dot(&other.w).clamp(-1.0, 1.0).acos();
+        if (theta_d + theta_b).min(PI) <= theta_a {
+            return (self.w, self.cos_theta_o);
+        }
+        if (theta_d + theta_a).min(PI) <= theta_b {
+            return (other.w, other.cos_theta_o);
+        }
+
+        let theta_o = (theta_a + theta_d + theta_b) / 2.0;
+        let axis = self.w.cross(&other.w);
+        if theta_o >= PI || axis.power() == 0.0 {
+            return (self.w, -1.0);
+        }
+        let w = rotate(&self.w, &axis.unit(), theta_o - theta_a);
+        (w, theta_o.cos())
+    }
+
+    /// Conservative estimate of the light reaching `p`, on a surface with normal `n`
+    /// if that isn't zero
+    pub fn importance(&self, p: &Point3, n: &Vec3) -> f64 {
+        let center = self.centroid();
+        let diagonal = self.bounds.max() - self.bounds.min();
+        let distance_squared = (*p - center).power().max(diagonal.len() / 2.0);
+
+        // Directions from the bounds towards p
+        let wi = (*p - center).unit();
+        let cos_theta_w = self.w.dot(&wi);
+        let sin_theta_w = sin_from_cos(cos_theta_w);
+
+        // Spread of those directions, through the bounding sphere of the bounds
+        let radius_squared = diagonal.power() / 4.0;
+        let cos_theta_b = if (*p - center).power() < radius_squared {
+            -1.0
+        } else {
+            (1.0 - radius_squared / (*p - center).power())
+                .max(0.0)
+                .sqrt()
+        };
+        let sin_theta_b = sin_from_cos(cos_theta_b);
+
+        let sin_theta_o = sin_from_cos(self.cos_theta_o);
+        let cos_theta_x = cos_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let sin_theta_x = sin_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let cos_theta_p = cos_sub_clamped(sin_theta_x, cos_theta_x, sin_theta_b, cos_theta_b);
+        // Strictly outside, as spots with a hard edge have no spread
+        if cos_theta_p < self.cos_theta_e {
+            return 0.0;
+        }
+
+        let mut importance = self.phi * cos_theta_p / distance_squared;
+        if *n != Vec3::zero() {
+            // Light may arrive on either side of the surface
+            let cos_theta_i = wi.dot(&n.unit()).abs();
+            let sin_theta_i = sin_from_cos(cos_theta_i);
+            importance *= cos_sub_clamped(sin_theta_i, cos_theta_i, sin_theta_b, cos_theta_b);
+        }
+        importance.max(0.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LightBvhNode {
+    bounds: LightBounds,
+    /// Index of the light for leaves, of the second child otherwise. The first child
+    /// follows its parent.
+    index: usize,
+    leaf: bool,
+}
+
+/// Hierarchy over lights with bounds, picking them by their estimated contribution
+/// to a point. Indices refer to the light list it was built from.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LightBvh {
+    nodes: Vec<LightBvhNode>,
+}
+
+impl LightBvh {
+    pub fn new(lights: &[LightEnum]) -> Self {
+        let mut bounded: Vec<(usize, LightBounds)> = lights
+            .iter()
+            .enumerate()
+            .filter_map(|(i, light)| light.bounds().map(|b| (i, b)))
+            .collect();
+        let mut bvh = Self { nodes: Vec::new() };
+        if !bounded.is_empty() {
+            bvh.build(&mut bounded);
+        }
+        bvh
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Splits at the median centroid along the axis where the centroids spread most
+    fn build(&mut self, lights: &mut [(usize, LightBounds)]) -> usize {
+        let node = self.nodes.len();
+        if lights.len() == 1 {
+            let (index, bounds) = lights[0].clone();
+            self.nodes.push(LightBvhNode {
+                bounds,
+                index,
+                leaf: true,
+            });
+            return node;
+        }
+
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = -min;
+        for (_, bounds) in lights.iter() {
+            let c = bounds.centroid();
+            for axis in 0..3 {
+                min[axis] = min[axis].min(c[axis]);
+                max[axis] = max[axis].max(c[axis]);
+            }
+        }
+        let extent = max - min;
+        let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        };
+        lights.sort_by(|a, b| {
+            a.1.centroid()[axis]
+                .partial_cmp(&b.1.centroid()[axis])
+                .unwrap()
+        });
+
+        let bounds = lights
+            .iter()
+            .skip(1)
+            .fold(lights[0].1.clone(), |acc, (_, b)| acc.union(b));
+        self.nodes.push(LightBvhNode {
+            bounds,
+            index: 0,
+            leaf: false,
+        });
+        let mid = lights.len() / 2;
+        let (left, right) = lights.split_at_mut(mid);
+        self.build(left);
+        self.nodes[node].index = self.build(right);
+        node
+    }
+
+    /// Chances of going down either child of the interior node `node`
+    fn child_probabilities(&self, node: usize, p: &Point3, n: &Vec3) -> Option<[f64; 2]> {
+        let left = self.nodes[node + 1].bounds.importance(p, n);
+        let right = self.nodes[self.nodes[node].index].bounds.importance(p, n);
+        if left + right <= 0.0 {
+            return None;
+        }
+        Some([left / (left + right), right / (left + right)])
+    }
+
+    /// Picks a light for the point `p` with normal `n`, which may be zero for points in
+    /// volumes, and returns its index and probability
+    pub fn sample(&self, p: &Point3, n: &Vec3) -> Option<(usize, f64)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut rng = random::rng();
+        let mut node = 0;
+        let mut pmf = 1.0;
+        loop {
+            if self.nodes[node].leaf {
+                if node == 0 && self.nodes[0].bounds.importance(p, n) <= 0.0 {
+                    return None;
+                }
+                return Some((self.nodes[node].index, pmf));
+            }
+            let [left, right] = self.child_probabilities(node, p, n)?;
+            if rng.gen_range(0.0..1.0) < left {
+                node += 1;
+                pmf *= left;
+            } else {
+                node = self.nodes[node].index;
+                pmf *= right;
+            }
+        }
+    }
+
+    /// Solid angle density with which `sample`, followed by sampling the chosen light,
+    /// picks `direction` from `p`. Only descends into nodes the ray can reach.
+    pub fn pdf(&self, lights: &[LightEnum], p: &Point3, n: &Vec3, direction: &Vec3) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+        if self.nodes[0].leaf && self.nodes[0].bounds.importance(p, n) <= 0.0 {
+            return 0.0;
+        }
+        let ray = Ray::new(p, direction, 0.0);
+        self.pdf_below(0, 1.0, lights, &ray, n)
+    }
+
+    fn pdf_below(&self, node: usize, pmf: f64, lights: &[LightEnum], ray: &Ray, n: &Vec3) -> f64 {
+        let current = &self.nodes[node];
+        if current.leaf {
+            return pmf * lights[current.index].pdf(&ray.origin, &ray.direction);
+        }
+        let probabilities = match self.child_probabilities(node, &ray.origin, n) {
+            Some(probabilities) => probabilities,
+            None => return 0.0,
+        };
+        [node + 1, current.index]
+            .iter()
+            .zip(probabilities.iter())
+            .filter(|(&child, &probability)| {
+                probability > 0.0 && self.nodes[child].bounds.bounds.hit(ray, 0.0, f64::INFINITY)
+            })
+            .map(|(&child, &probability)| self.pdf_below(child, pmf * probability, lights, ray, n))
+            .sum()
+    }
+
+    /// Index of the area light `ray` hits at `t`. Only descends into nodes around the hit.
+    pub fn light_at(&self, lights: &[LightEnum], ray: &Ray, t: f64) -> Option<usize> {
+        let p = ray.at(t);
+        let epsilon = 1e-6 * t.max(1.0);
+        let margin = 1e-6 * (1.0 + p.len());
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(node) = stack.pop() {
+            let current = &self.nodes[node];
+            let (min, max) = (current.bounds.bounds.min(), current.bounds.bounds.max());
+            if (0..3).any(|a| p[a] < min[a] - margin || p[a] > max[a] + margin) {
+                continue;
+            }
+            if !current.leaf {
+                stack.push(node + 1);
+                stack.push(current.index);
+                continue;
+            }
+            if let LightEnum::Area(area) = &lights[current.index] {
+                if area.hit(ray, t - epsilon, t + epsilon, &mut HitRecord::new()) {
+                    return Some(current.index);
+                }
+            }
+        }
+        None
+    }
+}
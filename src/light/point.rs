@@ -0,0 +1,148 @@
+use super::{EmissionSample, IesProfile, Light, LightBounds, LightSample};
+use crate::hittable::AABB;
+use crate::onb::Onb;
+use crate::random;
+use crate::spectrum::luminance;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Light emitted equally in all directions from `position`. With a radius it is a
+/// sphere of that size, casting soft shadows, which rays still pass through.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PointLight {
+    position: Point3,
+    /// Radiant intensity, power per unit solid angle
+    intensity: Color,
+    radius: f64,
+    /// Shapes the intensity around `axis`, see `with_profile`
+    profile: Option<Box<IesProfile>>,
+    axis: Vec3,
+    reference: Vec3,
+}
+
+impl PointLight {
+    pub fn new(position: &Point3, intensity: &Color) -> Self {
+        Self {
+            position: *position,
+            intensity: *intensity,
+            radius: 0.0,
+            profile: None,
+            axis: Vec3::new(0.0, -1.0, 0.0),
+            reference: Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Emits the measured distribution of `profile`, whose nadir points along `axis` and
+    /// horizontal angle 0 towards `reference`, with `intensity` in its brightest direction
+    pub fn with_profile(mut self, profile: &IesProfile, axis: &Vec3, reference: &Vec3) -> Self {
+        self.profile = Some(Box::new(profile.clone()));
+        self.axis = *axis;
+        self.reference = *reference;
+        self
+    }
+
+    pub fn position(&self) -> Point3 {
+        self.position
+    }
+
+    /// Intensity emitted along `direction`, pointing away from the light
+    fn intensity_towards(&self, direction: &Vec3) -> Color {
+        match &self.profile {
+            Some(profile) => {
+                profile.value_towards(&self.axis, &self.reference, direction) * self.intensity
+            }
+            None => self.intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, origin: &Point3) -> Option<LightSample> {
+        let to_light = self.position - *origin;
+        let distance_squared = to_light.power();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let intensity = self.intensity_towards(&-to_light);
+        if intensity == Color::zero() {
+            return None;
+        }
+        // From inside the sphere it is seen as a point at its center
+        if self.radius == 0.0 || distance_squared <= self.radius * self.radius {
+            let distance = distance_squared.sqrt();
+            return Some(LightSample {
+                direction: to_light / distance,
+                distance,
+                radiance: intensity / distance_squared,
+                pdf: 1.0,
+            });
+        }
+
+        // Uniform over the cone subtended by the sphere, whose radiance gives the
+        // same intensity as the point
+        let mut rng = random::rng();
+        let cos_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let cos_theta = 1.0 + rng.gen_range(0.0..1.0) * (cos_max - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+        let direction = Onb::build_from_w(&to_light).local(&Vec3::new(
+            phi.cos() * sin_theta,
+            phi.sin() * sin_theta,
+            cos_theta,
+        ));
+
+        // Distance to the near side of the sphere along `direction`
+        let distance = distance_squared.sqrt();
+        let along = distance * cos_theta;
+        let distance = along
+            - (self.radius * self.radius - distance_squared * sin_theta * sin_theta)
+                .max(0.0)
+                .sqrt();
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: intensity / (PI * self.radius * self.radius),
+            pdf: 1.0 / (2.0 * PI * (1.0 - cos_max)),
+        })
+    }
+
+    fn pdf(&self, _origin: &Point3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// From the center, also for a sphere
+    fn sample_emission(&self, _center: &Point3, _radius: f64) -> Option<EmissionSample> {
+        let direction = Vec3::rand_unit_vector();
+        Some(EmissionSample {
+            origin: self.position,
+            normal: Vec3::zero(),
+            direction,
+            radiance: self.intensity_towards(&direction),
+            pdf_position: 1.0,
+            pdf_direction: 1.0 / (4.0 * PI),
+        })
+    }
+
+    fn pdf_emission(&self, _origin: &Point3, _normal: &Vec3, _direction: &Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(LightBounds::omnidirectional(
+            AABB::new(&(self.position - r), &(self.position + r)),
+            4.0 * PI * luminance(&self.intensity),
+        ))
+    }
+}
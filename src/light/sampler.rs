@@ -0,0 +1,173 @@
+use super::{Light, LightBvh, LightEnum};
+use crate::random;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::Rng;
+
+/// Picks the light to sample at a shading point, either uniformly or through a
+/// `LightBvh` by estimated contribution. Lights without bounds (at infinity) are
+/// picked with the same chance as the whole hierarchy.
+///
+/// Paths traced from the lights have no point to pick lights for, and start from them
+/// by power instead, see `sample_emission`.
+pub struct LightSampler {
+    lights: Vec<LightEnum>,
+    bvh: LightBvh,
+    /// Picks every light with the same chance, leaving `bvh` for `area_light_at`
+    uniform: bool,
+    /// Indices of the lights outside `bvh`
+    unbounded: Vec<usize>,
+    /// Running sum of the chances with which `sample_emission` picks each light
+    emission_cdf: Vec<f64>,
+}
+
+impl LightSampler {
+    pub fn new(lights: Vec<LightEnum>) -> Self {
+        Self::build(lights, false)
+    }
+
+    /// Picks every light with the same chance, which only suits scenes with few lights
+    pub fn uniform(lights: Vec<LightEnum>) -> Self {
+        Self::build(lights, true)
+    }
+
+    fn build(lights: Vec<LightEnum>, uniform: bool) -> Self {
+        let bvh = LightBvh::new(&lights);
+        let unbounded = (0..lights.len())
+            .filter(|&i| lights[i].bounds().is_none())
+            .collect();
+        let mut sampler = Self {
+            lights,
+            bvh,
+            uniform,
+            unbounded,
+            emission_cdf: Vec::new(),
+        };
+        let pmf = sampler.emission_pmf();
+        sampler.emission_cdf = pmf
+            .iter()
+            .scan(0.0, |sum, p| {
+                *sum += p;
+                Some(*sum)
+            })
+            .collect();
+        sampler
+    }
+
+    /// Chances of starting a path from each light: in proportion to power among the
+    /// lights in the hierarchy, and split with the others as in `sample`
+    fn emission_pmf(&self) -> Vec<f64> {
+        let n = self.lights.len();
+        if self.uniform || n == 0 {
+            return vec![1.0 / n as f64; n];
+        }
+        let (p_unbounded, p_bvh) = self.split();
+        let power: Vec<Option<f64>> = self
+            .lights
+            .iter()
+            .map(|light| light.bounds().map(|b| b.phi.max(0.0)))
+            .collect();
+        let total: f64 = power.iter().flatten().sum();
+        let bounded = n - self.unbounded.len();
+        power
+            .iter()
+            .map(|phi| match phi {
+                None => p_unbounded,
+                Some(_) if total <= 0.0 => p_bvh / bounded as f64,
+                Some(phi) => p_bvh * phi / total,
+            })
+            .collect()
+    }
+
+    pub fn lights(&self) -> &[LightEnum] {
+        &self.lights
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Chance of picking one of the lights outside the hierarchy, and the hierarchy
+    fn split(&self) -> (f64, f64) {
+        let choices = self.unbounded.len() + if self.bvh.is_empty() { 0 } else { 1 };
+        let p = 1.0 / choices as f64;
+        (p, if self.bvh.is_empty() { 0.0 } else { p })
+    }
+
+    /// Picks a light to start a path from, and returns its index with the probability of
+    /// picking it
+    pub fn sample_emission(&self) -> Option<(usize, f64)> {
+        let total = *self.emission_cdf.last()?;
+        let u = random::rng().gen_range(0.0..1.0) * total;
+        let index = self
+            .emission_cdf
+            .partition_point(|&c| c <= u)
+            .min(self.lights.len() - 1);
+        Some((index, self.pmf_emission(index)))
+    }
+
+    /// Probability with which `sample_emission` picks the light at `index`
+    pub fn pmf_emission(&self, index: usize) -> f64 {
+        match index {
+            0 => self.emission_cdf[0],
+            _ => self.emission_cdf[index] - self.emission_cdf[index - 1],
+        }
+    }
+
+    /// Index of the area light `ray` hits at `t`, looked up through the hierarchy
+    pub fn area_light_at(&self, ray: &Ray, t: f64) -> Option<usize> {
+        self.bvh.light_at(&self.lights, ray, t)
+    }
+
+    /// Picks a light for the point `p` with normal `n`, which is zero for points in
+    /// volumes, and returns it with the probability of picking it
+    pub fn sample(&self, p: &Point3, n: &Vec3) -> Option<(&LightEnum, f64)> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let mut rng = random::rng();
+        if self.uniform {
+            let i = rng.gen_range(0..self.lights.len());
+            return Some((&self.lights[i], 1.0 / self.lights.len() as f64));
+        }
+
+        let (p_unbounded, p_bvh) = self.split();
+        let bvh_choices = if self.bvh.is_empty() { 0 } else { 1 };
+        let choice = rng.gen_range(0..self.unbounded.len() + bvh_choices);
+        if choice < self.unbounded.len() {
+            return Some((&self.lights[self.unbounded[choice]], p_unbounded));
+        }
+        self.bvh
+            .sample(p, n)
+            .map(|(i, pmf)| (&self.lights[i], pmf * p_bvh))
+    }
+
+    /// Solid angle density with which `sample`, followed by sampling the light it picks,
+    /// gives `direction` from `p`
+    pub fn pdf(&self, p: &Point3, n: &Vec3, direction: &Vec3) -> f64 {
+        if self.lights.is_empty() {
+            return 0.0;
+        }
+        if self.uniform {
+            return self
+                .lights
+                .iter()
+                .map(|light| light.pdf(p, direction))
+                .sum::<f64>()
+                / self.lights.len() as f64;
+        }
+
+        let (p_unbounded, p_bvh) = self.split();
+        let unbounded: f64 = self
+            .unbounded
+            .iter()
+            .map(|&i| self.lights[i].pdf(p, direction))
+            .sum();
+        let bounded = if p_bvh > 0.0 {
+            self.bvh.pdf(&self.lights, p, n, direction)
+        } else {
+            0.0
+        };
+        p_unbounded * unbounded + p_bvh * bounded
+    }
+}
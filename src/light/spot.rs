@@ -0,0 +1,150 @@
+use super::{EmissionSample, IesProfile, Light, LightBounds, LightSample};
+use crate::hittable::AABB;
+use crate::onb::Onb;
+use crate::random;
+use crate::spectrum::luminance;
+use crate::vec3::{Color, Point3, Vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Light emitted from `position` within a cone around `direction`, fading out
+/// smoothly towards the edge of the cone
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpotLight {
+    position: Point3,
+    direction: Vec3,
+    /// Radiant intensity along the axis of the cone
+    intensity: Color,
+    cos_cone: f64,
+    cos_falloff_start: f64,
+    profile: Option<Box<IesProfile>>,
+    reference: Vec3,
+}
+
+impl SpotLight {
+    /// `cone_angle` is the angle in degrees between the axis and the edge of the cone
+    pub fn new(position: &Point3, direction: &Vec3, intensity: &Color, cone_angle: f64) -> Self {
+        let cos_cone = crate::degrees_to_radians(cone_angle).cos();
+        Self {
+            position: *position,
+            direction: direction.unit(),
+            intensity: *intensity,
+            cos_cone,
+            cos_falloff_start: cos_cone,
+            profile: None,
+            reference: Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    /// Angle in degrees from the axis at which the light starts to fade out, the
+    /// whole cone by default
+    pub fn with_falloff_start(mut self, degrees: f64) -> Self {
+        self.cos_falloff_start = crate::degrees_to_radians(degrees).cos();
+        self
+    }
+
+    /// Shapes the light within the cone by the measured distribution of `profile`, whose
+    /// nadir points along the axis and horizontal angle 0 towards `reference`, with
+    /// `intensity` in its brightest direction
+    pub fn with_profile(mut self, profile: &IesProfile, reference: &Vec3) -> Self {
+        self.profile = Some(Box::new(profile.clone()));
+        self.reference = *reference;
+        self
+    }
+
+    pub fn position(&self) -> Point3 {
+        self.position
+    }
+
+    /// Fraction of the axial intensity emitted in a direction at `cos_theta` from the axis
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta >= self.cos_falloff_start {
+            return 1.0;
+        }
+        if cos_theta <= self.cos_cone {
+            return 0.0;
+        }
+        let t = (cos_theta - self.cos_cone) / (self.cos_falloff_start - self.cos_cone);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Intensity emitted along the unit vector `direction`, pointing away from the light
+    fn intensity_towards(&self, direction: &Vec3) -> Color {
+        let mut falloff = self.falloff(direction.dot(&self.direction));
+        if let Some(profile) = &self.profile {
+            falloff *= profile.value_towards(&self.direction, &self.reference, direction);
+        }
+        falloff * self.intensity
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, origin: &Point3) -> Option<LightSample> {
+        let to_light = self.position - *origin;
+        let distance_squared = to_light.power();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let intensity = self.intensity_towards(&-direction);
+        if intensity == Color::zero() {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: intensity / distance_squared,
+            pdf: 1.0,
+        })
+    }
+
+    fn pdf(&self, _origin: &Point3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Uniform over the cone
+    fn sample_emission(&self, _center: &Point3, _radius: f64) -> Option<EmissionSample> {
+        let mut rng = random::rng();
+        let cos_theta = 1.0 + rng.gen_range(0.0..1.0) * (self.cos_cone - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+        let direction = Onb::build_from_w(&self.direction).local(&Vec3::new(
+            phi.cos() * sin_theta,
+            phi.sin() * sin_theta,
+            cos_theta,
+        ));
+        Some(EmissionSample {
+            origin: self.position,
+            normal: Vec3::zero(),
+            direction,
+            radiance: self.intensity_towards(&direction),
+            pdf_position: 1.0,
+            pdf_direction: 1.0 / (2.0 * PI * (1.0 - self.cos_cone)),
+        })
+    }
+
+    fn pdf_emission(&self, _origin: &Point3, _normal: &Vec3, direction: &Vec3) -> f64 {
+        if direction.unit().dot(&self.direction) < self.cos_cone {
+            return 0.0;
+        }
+        1.0 / (2.0 * PI * (1.0 - self.cos_cone))
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    /// Full intensity within the falloff start, fading out over the rest of the cone
+    fn bounds(&self) -> Option<LightBounds> {
+        let spread = self.cos_cone.acos() - self.cos_falloff_start.acos();
+        Some(LightBounds {
+            bounds: AABB::new(&self.position, &self.position),
+            phi: 4.0 * PI * luminance(&self.intensity),
+            w: self.direction,
+            cos_theta_o: self.cos_falloff_start,
+            cos_theta_e: spread.cos(),
+        })
+    }
+}
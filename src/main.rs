@@ -2,7 +2,17 @@ use image::RgbImage;
 use rand::Rng;
 #[cfg(feature = "execution")]
 use ray_tracer_rs::progress;
-use ray_tracer_rs::{camera, hittable::HittableEnum, scenes, vec3};
+use ray_tracer_rs::{
+    background::Background,
+    camera,
+    film::Film,
+    hittable::HittableEnum,
+    integrator::{
+        Integrator, IntegratorEnum, LightSampling, PathSettings, PathTracer, RenderSettings,
+    },
+    light::{self, LightSampler},
+    scenes, vec3,
+};
 use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::Instant;
@@ -13,23 +23,82 @@ fn main() {
     let aspect_ratio = 1.0;
     // let width = 384;
     let width = 512;
-    let height = (width as f64 / aspect_ratio) as usize;
-    let samples_per_pixel = 50;
     let max_depth = 100;
+    // Trace four wavelengths per sample instead of RGB
+    let spectral = false;
+    let settings = RenderSettings {
+        width,
+        height: (width as f64 / aspect_ratio) as usize,
+        samples_per_pixel: 50,
+        integrator: IntegratorEnum::PathTracer(
+            PathTracer::new(
+                LightSampling::Mis,
+                PathSettings::new(max_depth).with_rr_depth(5),
+            )
+            .with_spectral(spectral),
+        ),
+        // NOTE: Integrators can also be picked by name, including the debug views
+        // "normals", "uv", "depth", "albedo", "ao" and "bvh"
+        // integrator: "normals".parse().unwrap(),
+        // NOTE: Photon mapping renders the whole image at once instead, for caustics.
+        // Its film holds a single sample per pixel, whatever samples_per_pixel says.
+        // integrator: IntegratorEnum::PhotonMapping(PhotonMapping::new(
+        //     PathSettings::new(max_depth),
+        //     64,
+        //     200_000,
+        // )),
+        // NOTE: Or Metropolis light transport, for light that paths rarely find
+        // integrator: IntegratorEnum::Mlt(Mlt::new(
+        //     PathTracer::new(LightSampling::Mis, PathSettings::new(max_depth)),
+        //     100,
+        // )),
+    };
+    let (width, height, samples_per_pixel) =
+        (settings.width, settings.height, settings.samples_per_pixel);
+    let integrator = Arc::new(settings.integrator);
 
     // NOTE: The following code is for the camera position for random scenes
     // let lookfrom = vec3::Point3::new(13.0, 2.0, 3.0);
     // let lookat = vec3::Point3::new(0.0, 0.0, 0.0);
-    // NOTE: The following code is for the camera position for the cornell box scene
+    // NOTE: The following code is for the camera position for the veach_mis scene
+    // let lookfrom = vec3::Point3::new(0.0, 2.0, 15.0);
+    // let lookat = vec3::Point3::new(0.0, 0.0, 0.0);
+    // NOTE: The following code is for the camera position for the many_lights scene
+    // let lookfrom = vec3::Point3::new(0.0, 10.0, 26.0);
+    // let lookat = vec3::Point3::new(0.0, 0.0, 0.0);
+    // NOTE: The following code is for the camera position for the fireball scene
+    // let lookfrom = vec3::Point3::new(0.0, 3.0, 14.0);
+    // let lookat = vec3::Point3::new(0.0, 2.5, 0.0);
+    // NOTE: The following code is for the camera position for the layered and thin_film
+    // scenes
+    // let lookfrom = vec3::Point3::new(12.0, 3.0, 0.0);
+    // let lookat = vec3::Point3::new(0.0, 1.0, 0.0);
+    // NOTE: The following code is for the camera position for the cornell box scenes,
+    // including cornell_ies
     let lookfrom = vec3::Point3::new(278.0, 278.0, -800.0);
     let lookat = vec3::Point3::new(278.0, 278.0, 0.0);
     let vup = vec3::Vec3::new(0.0, 1.0, 0.0);
     let dist_to_focus = 10.0;
     let aperture = 0.0;
-    let background = vec3::Color::zero();
+    let background = Background::Color(vec3::Color::zero());
+    // NOTE: Scenes can also be lit by an HDR environment map
+    // let background = Background::Environment(Box::new(
+    //     EnvironmentMap::new("images/environment.hdr")
+    //         .with_rotation(90.0)
+    //         .with_intensity(1.0),
+    // ));
+    // NOTE: Or by an analytic daylight sky with turbidity, sun elevation and azimuth
+    // let background = Background::Sky(Box::new(Sky::new(3.0, 40.0, 30.0)));
 
     let pb = Arc::new(RwLock::new(progress::ProgressBar::new(width * height)));
     let world: Arc<HittableEnum> = Arc::new(scenes::final_scene::scene());
+    #[allow(unused_mut)]
+    let mut lights = light::collect_lights(&world, &background);
+    // NOTE: Lights without geometry are added to the list, e.g. for punctual_lights
+    // lights.extend(scenes::punctual_lights::lights());
+    // NOTE: LightSampler::uniform picks lights without the light BVH, for comparison
+    let lights = Arc::new(LightSampler::new(lights));
+    let background = Arc::new(background);
     let cam = Arc::new(camera::Camera::new(
         lookfrom,
         lookat,
@@ -41,44 +110,73 @@ fn main() {
         0.0,
         1.0,
     ));
-    let buffer = Arc::new(RwLock::new(vec![0; width * height * 3]));
-    let handles: Vec<_> = (0..height)
-        .rev()
-        .map(|j| {
-            let buffer = Arc::clone(&buffer);
-            let world = Arc::clone(&world);
-            let cam = Arc::clone(&cam);
-            let pb = Arc::clone(&pb);
+    let start = Instant::now();
+    // Whole-image integrators render without the per-pixel loop below
+    let (film, samples) = match integrator.render(&cam, &background, &world, &lights, width, height)
+    {
+        Some(film) => (film, 1),
+        None => {
+            let film = Arc::new(RwLock::new(Film::new(width, height)));
+            let handles: Vec<_> = (0..height)
+                .rev()
+                .map(|j| {
+                    let film = Arc::clone(&film);
+                    let world = Arc::clone(&world);
+                    let lights = Arc::clone(&lights);
+                    let background = Arc::clone(&background);
+                    let integrator = Arc::clone(&integrator);
+                    let cam = Arc::clone(&cam);
+                    let pb = Arc::clone(&pb);
 
-            thread::spawn(move || {
-                let mut rng = rand::thread_rng();
-                for i in 0..width {
-                    let mut pixel_color = vec3::Color::zero();
-                    for _ in 0..samples_per_pixel {
-                        let u = (i as f64 + rng.gen_range(0.0..1.0)) / (width - 1) as f64;
-                        let v = (j as f64 + rng.gen_range(0.0..1.0)) / (height - 1) as f64;
-                        let r = cam.get_ray(u, v);
-                        pixel_color += r.color(&background, &world, max_depth);
-                    }
-                    let mut buf = buffer.write().unwrap();
-                    let (r, g, b) = pixel_color.get_color(samples_per_pixel);
-                    buf[(height - j - 1) * width * 3 + i * 3] = r;
-                    buf[(height - j - 1) * width * 3 + i * 3 + 1] = g;
-                    buf[(height - j - 1) * width * 3 + i * 3 + 2] = b;
-                    let mut pb = pb.write().unwrap();
-                    pb.update();
-                }
-            })
-        })
-        .collect();
+                    thread::spawn(move || {
+                        let mut rng = rand::thread_rng();
+                        let mut bounces = 0;
+                        for i in 0..width {
+                            let mut pixel_color = vec3::Color::zero();
+                            let mut splats = Vec::new();
+                            for _ in 0..samples_per_pixel {
+                                let u = (i as f64 + rng.gen_range(0.0..1.0)) / width as f64;
+                                let v = (j as f64 + rng.gen_range(0.0..1.0)) / height as f64;
+                                let r = cam.get_ray(u, v);
+                                let (l, stats) = integrator.color(
+                                    &r,
+                                    &cam,
+                                    &background,
+                                    &world,
+                                    &lights,
+                                    &mut splats,
+                                );
+                                pixel_color += l;
+                                bounces += stats.bounces;
+                            }
+                            let mut film = film.write().unwrap();
+                            film.add_sample(i, j, &pixel_color);
+                            for splat in &splats {
+                                film.add_splat(splat);
+                            }
+                            let mut pb = pb.write().unwrap();
+                            pb.update();
+                        }
+                        bounces
+                    })
+                })
+                .collect();
 
-    let start = Instant::now();
-    for handle in handles {
-        handle.join().unwrap();
-    }
+            let mut bounces = 0;
+            for handle in handles {
+                bounces += handle.join().unwrap();
+            }
+            println!(
+                "Average path length: {:.2}",
+                bounces as f64 / (width * height * samples_per_pixel) as f64
+            );
+            let film = Arc::try_unwrap(film).unwrap().into_inner().unwrap();
+            (film, samples_per_pixel)
+        }
+    };
     let duration = start.elapsed();
     println!("Time elapsed in expensive_function() is: {:?}", duration);
-    let image_buffer = Arc::try_unwrap(buffer).unwrap().into_inner().unwrap();
+    let image_buffer = film.to_rgb(samples);
     let img = RgbImage::from_raw(width as u32, height as u32, image_buffer)
         .expect("incorrect image buffer size");
 
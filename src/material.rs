@@ -1,5 +1,6 @@
 use crate::hittable;
 use crate::ray;
+use crate::spectrum::{SampledSpectrum, SampledWavelengths};
 use crate::vec3;
 
 pub mod dielectric;
@@ -11,23 +12,85 @@ pub use lambertian::Lambertian;
 pub mod metal;
 pub use metal::Metal;
 
+pub mod rough_dielectric;
+pub use rough_dielectric::RoughDielectric;
+
+pub mod subsurface;
+pub use subsurface::Subsurface;
+
+pub mod mix;
+pub use mix::Mix;
+
+pub mod coated;
+pub use coated::Coated;
+
+pub mod principled;
+pub use principled::{Param, Principled};
+
+pub mod conductor;
+pub use conductor::Conductor;
+
+pub mod microfacet;
+
 pub mod diffuse_light;
 pub use diffuse_light::DiffuseLight;
 
 pub mod isotropic;
 pub use isotropic::Isotropic;
 
+pub mod thin_film;
+pub use thin_film::ThinFilm;
+
+pub mod refractive_index;
+pub use refractive_index::RefractiveIndex;
+
+pub mod interior;
+pub use interior::{InteriorEntry, InteriorStack};
+
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+/// Outcome of sampling a material
+pub struct ScatterRecord {
+    pub scattered: ray::Ray,
+    /// BSDF times cosine over pdf: the weight of the radiance arriving along `scattered`
+    pub attenuation: vec3::Color,
+    /// Solid angle density of the scattered direction. Meaningless for delta lobes.
+    pub pdf: f64,
+    /// Sampled from a delta lobe (mirror, smooth glass, ...) that `eval` and `pdf` don't cover
+    pub is_delta: bool,
+}
+
+impl Default for ScatterRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScatterRecord {
+    pub fn new() -> Self {
+        ScatterRecord {
+            scattered: ray::Ray::new(&vec3::Point3::zero(), &vec3::Vec3::zero(), 0.0),
+            attenuation: vec3::Color::zero(),
+            pdf: 0.0,
+            is_delta: false,
+        }
+    }
+}
+
+/// Directions given to `eval` and `pdf` point away from the surface: `wi` is where light
+/// arrives from, and it leaves towards the origin of `r_in`.
 pub trait Material {
-    fn scatter(
-        &self,
-        r_in: &ray::Ray,
-        rec: &hittable::HitRecord,
-        attenuation: &mut vec3::Color,
-        scattered: &mut ray::Ray,
-    ) -> bool;
+    /// Samples a scattered ray. Returns false if the ray is absorbed.
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool;
+    /// BSDF (or phase function) times |cos| of `wi`, excluding delta lobes
+    fn eval(&self, _r_in: &ray::Ray, _rec: &hittable::HitRecord, _wi: &vec3::Vec3) -> vec3::Color {
+        vec3::Color::zero()
+    }
+    /// Solid angle density with which `sample` picks `wi`, excluding delta lobes
+    fn pdf(&self, _r_in: &ray::Ray, _rec: &hittable::HitRecord, _wi: &vec3::Vec3) -> f64 {
+        0.0
+    }
     fn emitted(&self, _u: f64, _v: f64, _p: &crate::vec3::Point3) -> crate::vec3::Color {
         vec3::Color::zero()
     }
@@ -40,22 +103,65 @@ pub enum MaterialEnum {
     Dielectric(Dielectric),
     DiffuseLight(DiffuseLight),
     Isotropic(Isotropic),
+    Conductor(Conductor),
+    RoughDielectric(RoughDielectric),
+    Principled(Box<Principled>),
+    Subsurface(Subsurface),
+    Mix(Box<Mix>),
+    Coated(Coated),
 }
 
 impl MaterialEnum {
-    pub fn scatter(
+    pub fn sample(
         &self,
         r_in: &ray::Ray,
         rec: &hittable::HitRecord,
-        attenuation: &mut vec3::Color,
-        scattered: &mut ray::Ray,
+        srec: &mut ScatterRecord,
     ) -> bool {
         match self {
-            MaterialEnum::Lambertian(l) => l.scatter(r_in, rec, attenuation, scattered),
-            MaterialEnum::Metal(m) => m.scatter(r_in, rec, attenuation, scattered),
-            MaterialEnum::Dielectric(d) => d.scatter(r_in, rec, attenuation, scattered),
-            MaterialEnum::DiffuseLight(dl) => dl.scatter(r_in, rec, attenuation, scattered),
-            MaterialEnum::Isotropic(i) => i.scatter(r_in, rec, attenuation, scattered),
+            MaterialEnum::Lambertian(l) => l.sample(r_in, rec, srec),
+            MaterialEnum::Metal(m) => m.sample(r_in, rec, srec),
+            MaterialEnum::Dielectric(d) => d.sample(r_in, rec, srec),
+            MaterialEnum::DiffuseLight(dl) => dl.sample(r_in, rec, srec),
+            MaterialEnum::Isotropic(i) => i.sample(r_in, rec, srec),
+            MaterialEnum::Conductor(c) => c.sample(r_in, rec, srec),
+            MaterialEnum::RoughDielectric(rd) => rd.sample(r_in, rec, srec),
+            MaterialEnum::Principled(p) => p.sample(r_in, rec, srec),
+            MaterialEnum::Subsurface(s) => s.sample(r_in, rec, srec),
+            MaterialEnum::Mix(m) => m.sample(r_in, rec, srec),
+            MaterialEnum::Coated(c) => c.sample(r_in, rec, srec),
+        }
+    }
+
+    pub fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        match self {
+            MaterialEnum::Lambertian(l) => l.eval(r_in, rec, wi),
+            MaterialEnum::Metal(m) => m.eval(r_in, rec, wi),
+            MaterialEnum::Dielectric(d) => d.eval(r_in, rec, wi),
+            MaterialEnum::DiffuseLight(dl) => dl.eval(r_in, rec, wi),
+            MaterialEnum::Isotropic(i) => i.eval(r_in, rec, wi),
+            MaterialEnum::Conductor(c) => c.eval(r_in, rec, wi),
+            MaterialEnum::RoughDielectric(rd) => rd.eval(r_in, rec, wi),
+            MaterialEnum::Principled(p) => p.eval(r_in, rec, wi),
+            MaterialEnum::Subsurface(s) => s.eval(r_in, rec, wi),
+            MaterialEnum::Mix(m) => m.eval(r_in, rec, wi),
+            MaterialEnum::Coated(c) => c.eval(r_in, rec, wi),
+        }
+    }
+
+    pub fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        match self {
+            MaterialEnum::Lambertian(l) => l.pdf(r_in, rec, wi),
+            MaterialEnum::Metal(m) => m.pdf(r_in, rec, wi),
+            MaterialEnum::Dielectric(d) => d.pdf(r_in, rec, wi),
+            MaterialEnum::DiffuseLight(dl) => dl.pdf(r_in, rec, wi),
+            MaterialEnum::Isotropic(i) => i.pdf(r_in, rec, wi),
+            MaterialEnum::Conductor(c) => c.pdf(r_in, rec, wi),
+            MaterialEnum::RoughDielectric(rd) => rd.pdf(r_in, rec, wi),
+            MaterialEnum::Principled(p) => p.pdf(r_in, rec, wi),
+            MaterialEnum::Subsurface(s) => s.pdf(r_in, rec, wi),
+            MaterialEnum::Mix(m) => m.pdf(r_in, rec, wi),
+            MaterialEnum::Coated(c) => c.pdf(r_in, rec, wi),
         }
     }
 
@@ -65,17 +171,92 @@ impl MaterialEnum {
             _ => vec3::Color::zero(),
         }
     }
+
+    pub fn emitted_spectrum(
+        &self,
+        u: f64,
+        v: f64,
+        p: &vec3::Point3,
+        lambdas: &SampledWavelengths,
+    ) -> SampledSpectrum {
+        match self {
+            MaterialEnum::DiffuseLight(dl) => dl.emitted_spectrum(u, v, p, lambdas),
+            _ => SampledSpectrum::zero(),
+        }
+    }
+
+    /// Scale of `emitted` towards `wo`, pointing away from the surface at `rec`
+    pub fn emission_falloff(&self, rec: &hittable::HitRecord, wo: &vec3::Vec3) -> f64 {
+        match self {
+            MaterialEnum::DiffuseLight(dl) => dl.falloff(rec, wo),
+            _ => 1.0,
+        }
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        matches!(self, MaterialEnum::DiffuseLight(_))
+    }
+
+    /// Interior of a ray that leaves `rec` through the surface, to the side opposite the
+    /// one `r_in` arrived from
+    pub fn interior_across(&self, r_in: &ray::Ray, rec: &hittable::HitRecord) -> InteriorStack {
+        let entry = match self {
+            MaterialEnum::Dielectric(d) => d.interior_entry(),
+            MaterialEnum::RoughDielectric(rd) => rd.interior_entry(),
+            MaterialEnum::Subsurface(s) => s.interior_entry(),
+            MaterialEnum::Principled(p) if p.has_transmission() => {
+                p.transmission_lobe(rec.u, rec.v, &rec.p).interior_entry()
+            }
+            MaterialEnum::Coated(c) => return c.base().interior_across(r_in, rec),
+            // Light goes through whichever part has an interior, the second if both do
+            MaterialEnum::Mix(m) if m.second().has_interior() => {
+                return m.second().interior_across(r_in, rec)
+            }
+            MaterialEnum::Mix(m) => return m.first().interior_across(r_in, rec),
+            _ => return r_in.interior.clone(),
+        };
+        r_in.interior
+            .cross_at(&entry, rec.front_face, r_in.wavelength)
+            .inside
+    }
+
+    /// Whether light going through the surface enters a volume of this material
+    pub fn has_interior(&self) -> bool {
+        match self {
+            MaterialEnum::Dielectric(_)
+            | MaterialEnum::RoughDielectric(_)
+            | MaterialEnum::Subsurface(_) => true,
+            MaterialEnum::Principled(p) => p.has_transmission(),
+            MaterialEnum::Mix(m) => m.first().has_interior() || m.second().has_interior(),
+            MaterialEnum::Coated(c) => c.base().has_interior(),
+            _ => false,
+        }
+    }
+
+    /// Whether scattering off this material depends on the wavelength
+    pub fn is_dispersive(&self) -> bool {
+        match self {
+            MaterialEnum::Dielectric(d) => d.is_dispersive() || d.has_thin_film(),
+            MaterialEnum::Metal(m) => m.has_thin_film(),
+            MaterialEnum::RoughDielectric(rd) => rd.is_dispersive(),
+            MaterialEnum::Mix(m) => m.first().is_dispersive() || m.second().is_dispersive(),
+            MaterialEnum::Coated(c) => c.base().is_dispersive(),
+            _ => false,
+        }
+    }
 }
 
 impl Material for MaterialEnum {
-    fn scatter(
-        &self,
-        r_in: &ray::Ray,
-        rec: &hittable::HitRecord,
-        attenuation: &mut vec3::Color,
-        scattered: &mut ray::Ray,
-    ) -> bool {
-        self.scatter(r_in, rec, attenuation, scattered)
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        self.sample(r_in, rec, srec)
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        self.eval(r_in, rec, wi)
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        self.pdf(r_in, rec, wi)
     }
 
     fn emitted(&self, u: f64, v: f64, p: &vec3::Point3) -> vec3::Color {
@@ -0,0 +1,87 @@
+use super::dielectric::schlick;
+use super::{reflect, Material, MaterialEnum, ScatterRecord};
+use crate::random;
+use crate::{hittable, ray, vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A smooth dielectric coat over another material, as on car paint or varnished wood.
+/// Light is reflected by the coat like a mirror, or goes through it to the base and back
+/// out, losing what the coat reflects at each crossing. Light reflected back down to the
+/// base from beneath the coat is left out.
+///
+/// Samples are reflected by the coat with the chance the coat reflects the incoming
+/// light, and go to the base otherwise.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Coated {
+    base: Box<MaterialEnum>,
+    ior: f64,
+}
+
+impl Coated {
+    pub fn new(base: MaterialEnum, ior: f64) -> Self {
+        Self {
+            base: Box::new(base),
+            ior,
+        }
+    }
+
+    pub fn base(&self) -> &MaterialEnum {
+        &self.base
+    }
+
+    /// Cosines of the incoming light at the surface, and of `wi`
+    fn cosines(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> (f64, f64) {
+        let cos_o = (-r_in.direction.unit()).dot(&rec.normal).clamp(0.0, 1.0);
+        let cos_i = wi.unit().dot(&rec.normal).abs().min(1.0);
+        (cos_o, cos_i)
+    }
+}
+
+impl Material for Coated {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        // The coat is on the outside only
+        if !rec.front_face {
+            return self.base.sample(r_in, rec, srec);
+        }
+
+        let unit_direction = r_in.direction.unit();
+        let cos_o = (-unit_direction).dot(&rec.normal).clamp(0.0, 1.0);
+        let coat = schlick(cos_o, self.ior);
+        if random::rng().gen_range(0.0..1.0) < coat {
+            // Reflected with the chance the coat reflects, which leaves no weight
+            srec.scattered = r_in.spawn(&rec.p, &reflect(&unit_direction, &rec.normal));
+            srec.attenuation = vec3::Color::new(1.0, 1.0, 1.0);
+            srec.pdf = 0.0;
+            srec.is_delta = true;
+            return true;
+        }
+
+        if !self.base.sample(r_in, rec, srec) {
+            return false;
+        }
+        // The coat lets light in with the chance of getting to the base, which cancels
+        // out, leaving the way out
+        let (_, cos_i) = self.cosines(r_in, rec, &srec.scattered.direction);
+        srec.attenuation *= 1.0 - schlick(cos_i, self.ior);
+        srec.pdf *= 1.0 - coat;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        if !rec.front_face {
+            return self.base.eval(r_in, rec, wi);
+        }
+        let (cos_o, cos_i) = self.cosines(r_in, rec, wi);
+        self.base.eval(r_in, rec, wi)
+            * ((1.0 - schlick(cos_o, self.ior)) * (1.0 - schlick(cos_i, self.ior)))
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        if !rec.front_face {
+            return self.base.pdf(r_in, rec, wi);
+        }
+        let (cos_o, _) = self.cosines(r_in, rec, wi);
+        (1.0 - schlick(cos_o, self.ior)) * self.base.pdf(r_in, rec, wi)
+    }
+}
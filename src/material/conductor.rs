@@ -0,0 +1,127 @@
+use super::microfacet::{fresnel_conductor, TrowbridgeReitz};
+use super::{reflect, Material, ScatterRecord};
+use crate::onb::Onb;
+use crate::random;
+use crate::{hittable, ray, vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Rough metal with a GGX microfacet distribution and complex IOR Fresnel
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Conductor {
+    distribution: TrowbridgeReitz,
+    eta: vec3::Color,
+    k: vec3::Color,
+}
+
+impl Conductor {
+    /// `eta` and `k` are given per color channel. Roughness is perceptual, in [0, 1],
+    /// along the direction in which the surface's `u` grows and across it.
+    pub fn new(eta: &vec3::Color, k: &vec3::Color, roughness_x: f64, roughness_y: f64) -> Self {
+        Self {
+            distribution: TrowbridgeReitz::from_roughness(roughness_x, roughness_y),
+            eta: *eta,
+            k: *k,
+        }
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            &vec3::Color::new(0.143, 0.374, 1.442),
+            &vec3::Color::new(3.983, 2.385, 1.603),
+            roughness,
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            &vec3::Color::new(0.200, 0.924, 1.102),
+            &vec3::Color::new(3.912, 2.452, 2.142),
+            roughness,
+            roughness,
+        )
+    }
+
+    pub fn aluminum(roughness: f64) -> Self {
+        Self::new(
+            &vec3::Color::new(1.657, 0.880, 0.521),
+            &vec3::Color::new(9.224, 6.270, 4.837),
+            roughness,
+            roughness,
+        )
+    }
+
+    fn fresnel(&self, cos_theta: f64) -> vec3::Color {
+        vec3::Color::new(
+            fresnel_conductor(cos_theta, self.eta.x(), self.k.x()),
+            fresnel_conductor(cos_theta, self.eta.y(), self.k.y()),
+            fresnel_conductor(cos_theta, self.eta.z(), self.k.z()),
+        )
+    }
+}
+
+impl Material for Conductor {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        let unit_direction = r_in.direction.unit();
+        if self.distribution.effectively_smooth() {
+            let reflected = reflect(&unit_direction, &rec.normal);
+            srec.scattered = r_in.spawn(&rec.p, &reflected);
+            srec.attenuation = self.fresnel((-unit_direction).dot(&rec.normal));
+            srec.pdf = 0.0;
+            srec.is_delta = true;
+            return true;
+        }
+
+        let uvw = Onb::build_from_w_u(&rec.normal, &rec.dpdu);
+        let wo = uvw.to_local(&(-unit_direction));
+        if wo.z() <= 0.0 {
+            return false;
+        }
+        let mut rng = random::rng();
+        let wm = self
+            .distribution
+            .sample_wm(&wo, rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+        let wi = reflect(&(-wo), &wm);
+        if wi.z() <= 0.0 {
+            return false;
+        }
+
+        // f cos / pdf of visible normal sampling reduces to F G2 / G1
+        srec.scattered = r_in.spawn(&rec.p, &uvw.local(&wi));
+        srec.attenuation =
+            self.fresnel(wo.dot(&wm)) * (self.distribution.g(&wo, &wi) / self.distribution.g1(&wo));
+        srec.pdf = self.distribution.d_visible(&wo, &wm) / (4.0 * wo.dot(&wm).abs());
+        srec.is_delta = false;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        if self.distribution.effectively_smooth() {
+            return vec3::Color::zero();
+        }
+        let uvw = Onb::build_from_w_u(&rec.normal, &rec.dpdu);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        let wi = uvw.to_local(&wi.unit());
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return vec3::Color::zero();
+        }
+        let wm = (wo + wi).unit();
+        self.fresnel(wo.dot(&wm))
+            * (self.distribution.d(&wm) * self.distribution.g(&wo, &wi) / (4.0 * wo.z()))
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        if self.distribution.effectively_smooth() {
+            return 0.0;
+        }
+        let uvw = Onb::build_from_w_u(&rec.normal, &rec.dpdu);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        let wi = uvw.to_local(&wi.unit());
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let wm = (wo + wi).unit();
+        self.distribution.d_visible(&wo, &wm) / (4.0 * wo.dot(&wm).abs())
+    }
+}
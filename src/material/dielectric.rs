@@ -1,9 +1,10 @@
-use super::{reflect, refract, Material};
+use super::{reflect, refract, InteriorEntry, Material, RefractiveIndex, ScatterRecord, ThinFilm};
+use crate::random;
 use crate::{hittable, ray, vec3};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-fn schlick(cosine: f64, ref_idx: f64) -> f64 {
+pub(super) fn schlick(cosine: f64, ref_idx: f64) -> f64 {
     let mut r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
     r0 = r0 * r0;
     r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
@@ -11,46 +12,175 @@ fn schlick(cosine: f64, ref_idx: f64) -> f64 {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Dielectric {
-    ref_idx: f64,
+    ior: RefractiveIndex,
+    priority: u32,
+    absorption: vec3::Color,
+    thin_film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     pub fn new(ref_idx: f64) -> Self {
-        Self { ref_idx }
+        Self::with_ior(RefractiveIndex::Constant(ref_idx))
+    }
+
+    pub fn with_ior(ior: RefractiveIndex) -> Self {
+        Self {
+            ior,
+            priority: 0,
+            absorption: vec3::Color::zero(),
+            thin_film: None,
+        }
+    }
+
+    /// n = a + b / λ², with λ in µm
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Self::with_ior(RefractiveIndex::Cauchy { a, b })
+    }
+
+    /// n² = 1 + Σ b_i λ² / (λ² - c_i), with λ in µm
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self::with_ior(RefractiveIndex::Sellmeier { b, c })
+    }
+
+    /// Schott N-BK7 borosilicate crown glass
+    pub fn crown_glass() -> Self {
+        Self::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+
+    /// Schott SF11 dense flint glass, strongly dispersive
+    pub fn flint_glass() -> Self {
+        Self::sellmeier(
+            [1.73759695, 0.313747346, 1.89878101],
+            [0.013188707, 0.0623068142, 155.23629],
+        )
+    }
+
+    pub fn diamond() -> Self {
+        Self::sellmeier([4.3356, 0.3306, 0.0], [0.011236, 0.030625, 0.0])
+    }
+
+    /// Glass of index 1.5 that lets `tint` through after travelling `distance`
+    pub fn tinted_glass(tint: &vec3::Color, distance: f64) -> Self {
+        Self::new(1.5).with_absorption(tint, distance)
+    }
+
+    /// Where volumes overlap, the one with the higher priority wins.
+    /// Surfaces of a lower priority volume inside a higher priority one are ignored.
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Light travelling `distance` through the interior is attenuated to `color`.
+    /// Shorter distances give deeper colors for the same `color`. `distance` must be
+    /// positive.
+    pub fn with_absorption(mut self, color: &vec3::Color, distance: f64) -> Self {
+        assert!(distance > 0.0, "Absorption distance must be positive");
+        let coefficient = |c: f64| -c.clamp(1e-6, 1.0).ln() / distance;
+        self.absorption = vec3::Color::new(
+            coefficient(color.x()),
+            coefficient(color.y()),
+            coefficient(color.z()),
+        );
+        self
+    }
+
+    /// Iridescent film on the surface, as on a soap bubble or a coated lens
+    pub fn with_thin_film(mut self, thin_film: ThinFilm) -> Self {
+        self.thin_film = Some(thin_film);
+        self
+    }
+
+    pub fn has_thin_film(&self) -> bool {
+        self.thin_film.is_some()
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        self.ior.is_dispersive()
+    }
+
+    pub(super) fn interior_entry(&self) -> InteriorEntry {
+        InteriorEntry {
+            ior: self.ior.clone(),
+            priority: self.priority,
+            absorption: self.absorption,
+            scattering: vec3::Color::zero(),
+        }
     }
 }
 
 impl Material for Dielectric {
-    fn scatter(
-        &self,
-        r_in: &ray::Ray,
-        rec: &hittable::HitRecord,
-        attenuation: &mut vec3::Color,
-        scattered: &mut ray::Ray,
-    ) -> bool {
-        *attenuation = vec3::Color::new(1.0, 1.0, 1.0);
-        let etai_over_etat = if rec.front_face {
-            1.0 / self.ref_idx
-        } else {
-            self.ref_idx
-        };
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        srec.pdf = 0.0;
+        srec.is_delta = true;
+        let crossing = r_in
+            .interior
+            .cross(&self.interior_entry(), rec.front_face, r_in.wavelength);
+
+        // A surface hidden inside a higher priority volume is a false intersection:
+        // the ray continues straight on and only the interior changes
+        if crossing.hidden {
+            srec.attenuation = vec3::Color::new(1.0, 1.0, 1.0);
+            srec.scattered = r_in.spawn(&rec.p, &r_in.direction);
+            srec.scattered.interior = crossing.inside;
+            return true;
+        }
+
+        // Dispersion splits the path: continue with a single sampled wavelength
+        srec.attenuation = crossing.weight;
+        let wavelength = crossing.wavelength;
+        let etai_over_etat = crossing.etai_over_etat;
         let unit_direction = r_in.direction.unit();
         let cos_theta = (-unit_direction).dot(&rec.normal);
         if cos_theta < 0.0 {
             panic!("cos_theta must be positive!");
         }
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
-        if etai_over_etat * sin_theta > 1.0
-            || rand::thread_rng().gen_range(0.0..1.0) < schlick(cos_theta, etai_over_etat)
-        {
+        if etai_over_etat * sin_theta > 1.0 {
             // Must reflect if total internal reflection
-            // Can reflect if Schlick says so
             let reflected = reflect(&unit_direction, &rec.normal);
-            *scattered = ray::Ray::new(&rec.p, &reflected, r_in.time);
+            srec.scattered = r_in.spawn(&rec.p, &reflected);
+            srec.scattered.wavelength = wavelength;
             return true;
         }
+
+        // Reflect or refract with the chance the film or Schlick gives, weighted by the
+        // film's reflectance of each color over that chance
+        let (reflect_chance, reflectance) = match &self.thin_film {
+            Some(film) => {
+                let outside = r_in.interior.current_ref_idx(wavelength);
+                let reflectance = film.reflectance_dielectric(
+                    rec,
+                    wavelength,
+                    cos_theta,
+                    outside,
+                    outside / etai_over_etat,
+                );
+                let chance = (reflectance.x() + reflectance.y() + reflectance.z()) / 3.0;
+                (chance, Some(reflectance))
+            }
+            None => (schlick(cos_theta, etai_over_etat), None),
+        };
+        if random::rng().gen_range(0.0..1.0) < reflect_chance {
+            let reflected = reflect(&unit_direction, &rec.normal);
+            srec.scattered = r_in.spawn(&rec.p, &reflected);
+            srec.scattered.wavelength = wavelength;
+            if let Some(reflectance) = reflectance {
+                srec.attenuation = srec.attenuation * reflectance / reflect_chance;
+            }
+            return true;
+        }
+        if let Some(reflectance) = reflectance {
+            srec.attenuation = srec.attenuation * (vec3::Color::new(1.0, 1.0, 1.0) - reflectance)
+                / (1.0 - reflect_chance);
+        }
         let refracted = refract(&unit_direction, &rec.normal, etai_over_etat);
-        *scattered = ray::Ray::new(&rec.p, &refracted, r_in.time);
+        srec.scattered = r_in.spawn(&rec.p, &refracted);
+        srec.scattered.interior = crossing.inside;
+        srec.scattered.wavelength = wavelength;
         true
     }
 }
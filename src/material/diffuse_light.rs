@@ -1,25 +1,88 @@
-pub use super::Material;
+pub use super::{Material, ScatterRecord};
+use crate::light::IesProfile;
+use crate::spectrum::{Blackbody, SampledSpectrum, SampledWavelengths};
+use crate::texture::SolidColor;
 pub use crate::texture::TextureEnum;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DiffuseLight {
     pub emit: TextureEnum,
+    blackbody: Option<Blackbody>,
+    profile: Option<Box<IesProfile>>,
+    reference: crate::vec3::Vec3,
 }
 
 impl DiffuseLight {
     pub fn new(emit: &TextureEnum) -> Self {
-        Self { emit: emit.clone() }
+        Self {
+            emit: emit.clone(),
+            blackbody: None,
+            profile: None,
+            reference: crate::vec3::Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    /// Emits the spectrum of a blackbody at `temperature` (K), peaking at `intensity`
+    pub fn blackbody(temperature: f64, intensity: f64) -> Self {
+        let blackbody = Blackbody::new(temperature, intensity);
+        Self {
+            emit: TextureEnum::SolidColor(SolidColor::new(blackbody.rgb())),
+            blackbody: Some(blackbody),
+            profile: None,
+            reference: crate::vec3::Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    /// Emits the measured distribution of `profile` with its nadir along the surface
+    /// normal and horizontal angle 0 towards `reference`. Straight along the normal the
+    /// radiance is `emit` times the profile.
+    pub fn with_profile(mut self, profile: &IesProfile, reference: &crate::vec3::Vec3) -> Self {
+        self.profile = Some(Box::new(profile.clone()));
+        self.reference = *reference;
+        self
     }
-}
 
+    /// Scale of the emitted radiance towards `wo`, pointing away from the surface. With a
+    /// profile only the front of the surface emits.
+    pub fn falloff(&self, rec: &crate::hittable::HitRecord, wo: &crate::vec3::Vec3) -> f64 {
+        match &self.profile {
+            // The profile gives the intensity, which is radiance times the projected area
+            Some(profile) => {
+                if !rec.front_face {
+                    return 0.0;
+                }
+                // The normal faces `wo`, which is outward on the front face
+                let outward_normal = rec.normal;
+                let cos_theta = outward_normal.dot(&wo.unit());
+                if cos_theta <= 0.0 {
+                    return 0.0;
+                }
+                profile.value_towards(&outward_normal, &self.reference, wo) / cos_theta
+            }
+            None => 1.0,
+        }
+    }
+
+    pub fn emitted_spectrum(
+        &self,
+        u: f64,
+        v: f64,
+        p: &crate::vec3::Point3,
+        lambdas: &SampledWavelengths,
+    ) -> SampledSpectrum {
+        match &self.blackbody {
+            Some(blackbody) => blackbody.sample(lambdas),
+            None => SampledSpectrum::from_rgb_illuminant(&self.emit.value(u, v, p), lambdas),
+        }
+    }
+}
 impl Material for DiffuseLight {
-    fn scatter(
+    fn sample(
         &self,
         _r_in: &crate::ray::Ray,
         _rec: &crate::hittable::HitRecord,
-        _attenuation: &mut crate::vec3::Color,
-        _scattered: &mut crate::ray::Ray,
+        _srec: &mut ScatterRecord,
     ) -> bool {
         false
     }
@@ -0,0 +1,200 @@
+use super::RefractiveIndex;
+use crate::random;
+use crate::spectrum;
+use crate::vec3::Color;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A refractive volume the ray is currently travelling through.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InteriorEntry {
+    pub ior: RefractiveIndex,
+    pub priority: u32,
+    /// Absorption coefficient per unit length for each color channel
+    pub absorption: Color,
+    /// Scattering coefficient per unit length for each color channel
+    pub scattering: Color,
+}
+
+impl InteriorEntry {
+    /// Beer-Lambert transmittance over `distance` inside this volume, of the light that
+    /// is neither absorbed nor scattered away
+    pub fn transmittance(&self, distance: f64) -> Color {
+        let extinction = self.absorption + self.scattering;
+        Color::new(
+            (-extinction.x() * distance).exp(),
+            (-extinction.y() * distance).exp(),
+            (-extinction.z() * distance).exp(),
+        )
+    }
+
+    /// Samples where a ray going `distance` through this volume scatters, if before
+    /// then, along with the weight of the outcome. The distance is sampled for a color
+    /// channel picked at random, and weighed by the density averaged over all of them.
+    pub fn sample_scattering(&self, distance: f64) -> (Option<f64>, Color) {
+        if self.scattering == Color::zero() {
+            return (None, self.transmittance(distance));
+        }
+        let extinction = self.absorption + self.scattering;
+        let mut rng = random::rng();
+        let channel = rng.gen_range(0..3);
+        let t = -(1.0 - rng.gen_range(0.0..1.0_f64)).ln() / extinction[channel];
+
+        if t < distance {
+            let transmittance = self.transmittance(t);
+            let density = extinction * transmittance;
+            let pdf = (density.x() + density.y() + density.z()) / 3.0;
+            (Some(t), self.scattering * transmittance / pdf)
+        } else {
+            let transmittance = self.transmittance(distance);
+            let probability = (transmittance.x() + transmittance.y() + transmittance.z()) / 3.0;
+            if probability == 0.0 {
+                return (None, Color::zero());
+            }
+            (None, transmittance / probability)
+        }
+    }
+}
+
+/// Stack of the refractive volumes enclosing a ray.
+///
+/// Overlapping volumes are resolved by priority: the entry with the highest
+/// priority defines the medium, and on ties the most recently entered wins.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InteriorStack {
+    entries: Vec<InteriorEntry>,
+}
+
+impl InteriorStack {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn push(&mut self, entry: InteriorEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Removes the most recently entered volume equal to `entry`.
+    /// Returns false if the ray was not inside such a volume.
+    pub fn remove(&mut self, entry: &InteriorEntry) -> bool {
+        match self.entries.iter().rposition(|e| e == entry) {
+            Some(i) => {
+                self.entries.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The volume that currently defines the medium, if any.
+    pub fn current(&self) -> Option<&InteriorEntry> {
+        let mut current: Option<&InteriorEntry> = None;
+        for e in &self.entries {
+            if current.is_none_or(|c| e.priority >= c.priority) {
+                current = Some(e);
+            }
+        }
+        current
+    }
+
+    pub fn current_ref_idx(&self, wavelength: Option<f64>) -> f64 {
+        self.current().map_or(1.0, |e| e.ior.at(wavelength))
+    }
+
+    /// See `InteriorEntry::sample_scattering`
+    pub fn sample_scattering(&self, distance: f64) -> (Option<f64>, Color) {
+        self.current()
+            .map_or((None, Color::new(1.0, 1.0, 1.0)), |e| {
+                e.sample_scattering(distance)
+            })
+    }
+
+    pub fn transmittance(&self, distance: f64) -> Color {
+        self.current()
+            .map_or(Color::new(1.0, 1.0, 1.0), |e| e.transmittance(distance))
+    }
+
+    pub fn highest_priority(&self) -> Option<u32> {
+        self.current().map(|e| e.priority)
+    }
+}
+
+/// What a ray sees when it reaches the boundary of a refractive volume
+pub struct Crossing {
+    /// Interior of a ray continuing through the boundary
+    pub inside: InteriorStack,
+    /// The boundary lies inside a higher priority volume and must be ignored
+    pub hidden: bool,
+    /// Either side of the boundary disperses light
+    pub dispersive: bool,
+    /// Relative index of refraction, incident side over transmitted side
+    pub etai_over_etat: f64,
+    pub wavelength: Option<f64>,
+    /// RGB weight of the wavelength sampled at this boundary, white if none was
+    pub weight: Color,
+}
+
+impl InteriorStack {
+    /// Resolves the boundary of the volume `entry`, entered if `front_face`, left otherwise.
+    /// If either side disperses light, the path is narrowed down to a single sampled wavelength.
+    pub fn cross(
+        &self,
+        entry: &InteriorEntry,
+        front_face: bool,
+        wavelength: Option<f64>,
+    ) -> Crossing {
+        let crossing = self.cross_at(entry, front_face, wavelength);
+        if crossing.hidden || !crossing.dispersive || wavelength.is_some() {
+            return crossing;
+        }
+        let lambda = spectrum::sample_wavelength();
+        let mut crossing = self.cross_at(entry, front_face, Some(lambda));
+        crossing.weight = spectrum::wavelength_to_rgb(lambda);
+        crossing
+    }
+
+    /// Like `cross`, but never samples a wavelength. Without one, dispersive
+    /// volumes use their index at the sodium D line.
+    pub fn cross_at(
+        &self,
+        entry: &InteriorEntry,
+        front_face: bool,
+        wavelength: Option<f64>,
+    ) -> Crossing {
+        let mut inside = self.clone();
+        if front_face {
+            inside.push(entry.clone());
+        } else {
+            inside.remove(entry);
+        }
+
+        let outside = if front_face { self } else { &inside };
+        let hidden = outside
+            .highest_priority()
+            .is_some_and(|priority| priority > entry.priority);
+        let dispersive =
+            entry.ior.is_dispersive() || outside.current().is_some_and(|e| e.ior.is_dispersive());
+
+        let ref_idx = entry.ior.at(wavelength);
+        let etai_over_etat = if front_face {
+            self.current_ref_idx(wavelength) / ref_idx
+        } else {
+            ref_idx / inside.current_ref_idx(wavelength)
+        };
+
+        Crossing {
+            inside,
+            hidden,
+            dispersive,
+            etai_over_etat,
+            wavelength,
+            weight: Color::new(1.0, 1.0, 1.0),
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use super::Material;
+use super::{Material, ScatterRecord};
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
 use crate::texture::TextureEnum;
 use crate::vec3::{Color, Vec3};
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Isotropic {
@@ -19,15 +20,19 @@ impl Isotropic {
 }
 
 impl Material for Isotropic {
-    fn scatter(
-        &self,
-        r_in: &Ray,
-        rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool {
-        *scattered = Ray::new(&rec.p, &Vec3::rand_unit_sphere(), r_in.time);
-        *attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
+    fn sample(&self, r_in: &Ray, rec: &HitRecord, srec: &mut ScatterRecord) -> bool {
+        srec.scattered = r_in.spawn(&rec.p, &Vec3::rand_unit_sphere());
+        srec.attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
+        srec.pdf = 1.0 / (4.0 * PI);
+        srec.is_delta = false;
         true
     }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, _wi: &Vec3) -> Color {
+        self.albedo.value(rec.u, rec.v, &rec.p) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _r_in: &Ray, _rec: &HitRecord, _wi: &Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
 }
@@ -1,7 +1,8 @@
-use super::Material;
+use super::{Material, ScatterRecord};
 use crate::texture::TextureEnum;
 use crate::{hittable, ray, vec3};
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Lambertian {
@@ -17,16 +18,21 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(
-        &self,
-        r_in: &ray::Ray,
-        rec: &hittable::HitRecord,
-        attenuation: &mut vec3::Color,
-        scattered: &mut ray::Ray,
-    ) -> bool {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
         let scatter_direction = rec.normal + vec3::Vec3::rand_unit_vector();
-        *scattered = ray::Ray::new(&rec.p, &scatter_direction, r_in.time);
-        *attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
+        srec.scattered = r_in.spawn(&rec.p, &scatter_direction);
+        srec.attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
+        srec.pdf = self.pdf(r_in, rec, &scatter_direction.unit());
+        srec.is_delta = false;
         true
     }
+
+    fn eval(&self, _r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        let cosine = rec.normal.dot(wi).max(0.0);
+        self.albedo.value(rec.u, rec.v, &rec.p) * (cosine / PI)
+    }
+
+    fn pdf(&self, _r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        rec.normal.dot(wi).max(0.0) / PI
+    }
 }
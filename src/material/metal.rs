@@ -1,37 +1,128 @@
-use super::{reflect, Material};
+use super::microfacet::TrowbridgeReitz;
+use super::{reflect, Material, ScatterRecord, ThinFilm};
+use crate::onb::Onb;
+use crate::random;
 use crate::{hittable, ray, vec3};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Metal {
     albedo: vec3::Color,
     fuzz: f64,
+    thin_film: Option<ThinFilm>,
 }
 
 impl Metal {
+    /// A mirror for a `fuzz` of 0. Fuzzy metals scatter around the mirror direction with
+    /// a GGX distribution of microfacets, taking `fuzz` as its perceptual roughness
+    /// in [0, 1].
     pub fn new(albedo: &vec3::Color, fuzz: f64) -> Self {
         Self {
             albedo: *albedo,
             fuzz,
+            thin_film: None,
         }
     }
-}
 
-impl Material for Metal {
-    fn scatter(
+    /// Iridescent film over the metal, as on heat-tinted steel. The film sees the metal as
+    /// reflecting `albedo` with a phase shift of half a wave, i.e. a real amplitude
+    /// coefficient of -sqrt(albedo) per channel, leaving out the phase shift of a real
+    /// metal's complex index.
+    pub fn with_thin_film(mut self, thin_film: ThinFilm) -> Self {
+        self.thin_film = Some(thin_film);
+        self
+    }
+
+    pub fn has_thin_film(&self) -> bool {
+        self.thin_film.is_some()
+    }
+
+    fn distribution(&self) -> TrowbridgeReitz {
+        let roughness = self.fuzz.clamp(0.0, 1.0);
+        TrowbridgeReitz::from_roughness(roughness, roughness)
+    }
+
+    /// Reflectance for light arriving at `cos_theta` from the (micro)facet normal
+    fn reflectance(
         &self,
         r_in: &ray::Ray,
         rec: &hittable::HitRecord,
-        attenuation: &mut vec3::Color,
-        scattered: &mut ray::Ray,
-    ) -> bool {
-        let reflected = reflect(&r_in.direction.unit(), &rec.normal);
-        *scattered = ray::Ray::new(
-            &rec.p,
-            &(reflected + self.fuzz * vec3::Vec3::rand_unit_sphere()),
-            r_in.time,
-        );
-        *attenuation = self.albedo;
-        scattered.direction.dot(&rec.normal) > 0.0
+        cos_theta: f64,
+    ) -> vec3::Color {
+        match &self.thin_film {
+            Some(film) => {
+                let outside = r_in.interior.current_ref_idx(r_in.wavelength);
+                film.reflectance_metal(rec, r_in.wavelength, cos_theta, outside, &self.albedo)
+            }
+            None => self.albedo,
+        }
+    }
+}
+
+impl Material for Metal {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        let unit_direction = r_in.direction.unit();
+        let distribution = self.distribution();
+        if distribution.effectively_smooth() {
+            let reflected = reflect(&unit_direction, &rec.normal);
+            srec.scattered = r_in.spawn(&rec.p, &reflected);
+            let cos_theta = (-unit_direction).dot(&rec.normal).clamp(0.0, 1.0);
+            srec.attenuation = self.reflectance(r_in, rec, cos_theta);
+            srec.pdf = 0.0;
+            srec.is_delta = true;
+            return true;
+        }
+
+        let uvw = Onb::build_from_w(&rec.normal);
+        let wo = uvw.to_local(&(-unit_direction));
+        if wo.z() <= 0.0 {
+            return false;
+        }
+        let mut rng = random::rng();
+        let wm = distribution.sample_wm(&wo, rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+        let wi = reflect(&(-wo), &wm);
+        if wi.z() <= 0.0 {
+            return false;
+        }
+
+        // f cos / pdf of visible normal sampling reduces to F G2 / G1
+        srec.scattered = r_in.spawn(&rec.p, &uvw.local(&wi));
+        srec.attenuation = self.reflectance(r_in, rec, wo.dot(&wm))
+            * (distribution.g(&wo, &wi) / distribution.g1(&wo));
+        srec.pdf = distribution.d_visible(&wo, &wm) / (4.0 * wo.dot(&wm).abs());
+        srec.is_delta = false;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        let distribution = self.distribution();
+        if distribution.effectively_smooth() {
+            return vec3::Color::zero();
+        }
+        let uvw = Onb::build_from_w(&rec.normal);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        let wi = uvw.to_local(&wi.unit());
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return vec3::Color::zero();
+        }
+        let wm = (wo + wi).unit();
+        self.reflectance(r_in, rec, wo.dot(&wm))
+            * (distribution.d(&wm) * distribution.g(&wo, &wi) / (4.0 * wo.z()))
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        let distribution = self.distribution();
+        if distribution.effectively_smooth() {
+            return 0.0;
+        }
+        let uvw = Onb::build_from_w(&rec.normal);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        let wi = uvw.to_local(&wi.unit());
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let wm = (wo + wi).unit();
+        distribution.d_visible(&wo, &wm) / (4.0 * wo.dot(&wm).abs())
     }
 }
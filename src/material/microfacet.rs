@@ -0,0 +1,149 @@
+use crate::vec3::Vec3;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+// All directions are in the local shading frame, with the normal along +z.
+
+/// Trowbridge-Reitz (GGX) distribution of microfacet normals
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TrowbridgeReitz {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl TrowbridgeReitz {
+    pub fn new(alpha_x: f64, alpha_y: f64) -> Self {
+        Self {
+            alpha_x: alpha_x.max(1e-4),
+            alpha_y: alpha_y.max(1e-4),
+        }
+    }
+
+    /// Perceptual roughness in [0, 1] mapped to alpha = roughness²
+    pub fn from_roughness(roughness_x: f64, roughness_y: f64) -> Self {
+        Self::new(roughness_x * roughness_x, roughness_y * roughness_y)
+    }
+
+    /// Below this the surface is rendered as a perfect mirror
+    pub fn effectively_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < 1e-3
+    }
+
+    pub fn d(&self, wm: &Vec3) -> f64 {
+        let cos2 = wm.z() * wm.z();
+        if cos2 == 0.0 {
+            return 0.0;
+        }
+        let e = (wm.x() * wm.x() / (self.alpha_x * self.alpha_x)
+            + wm.y() * wm.y() / (self.alpha_y * self.alpha_y))
+            / cos2;
+        1.0 / (PI * self.alpha_x * self.alpha_y * cos2 * cos2 * (1.0 + e) * (1.0 + e))
+    }
+
+    fn lambda(&self, w: &Vec3) -> f64 {
+        let cos2 = w.z() * w.z();
+        if cos2 == 0.0 {
+            return f64::INFINITY;
+        }
+        let alpha2_tan2 = (w.x() * w.x() * self.alpha_x * self.alpha_x
+            + w.y() * w.y() * self.alpha_y * self.alpha_y)
+            / cos2;
+        ((1.0 + alpha2_tan2).sqrt() - 1.0) / 2.0
+    }
+
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Height-correlated masking-shadowing
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Distribution of normals visible from `w`
+    pub fn d_visible(&self, w: &Vec3, wm: &Vec3) -> f64 {
+        if w.z() == 0.0 {
+            return 0.0;
+        }
+        self.g1(w) / w.z().abs() * self.d(wm) * w.dot(wm).abs()
+    }
+
+    /// Samples a visible normal from `w` (Heitz, "Sampling the GGX Distribution of Visible Normals")
+    pub fn sample_wm(&self, w: &Vec3, u1: f64, u2: f64) -> Vec3 {
+        let flip = w.z() < 0.0;
+        let w = if flip { -*w } else { *w };
+        let vh = Vec3::new(self.alpha_x * w.x(), self.alpha_y * w.y(), w.z()).unit();
+
+        let lensq = vh.x() * vh.x() + vh.y() * vh.y();
+        let t1 = if lensq > 0.0 {
+            Vec3::new(-vh.y(), vh.x(), 0.0) / lensq.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = vh.cross(&t1);
+
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+
+        let wm = Vec3::new(
+            self.alpha_x * nh.x(),
+            self.alpha_y * nh.y(),
+            nh.z().max(1e-6),
+        )
+        .unit();
+        if flip {
+            -wm
+        } else {
+            wm
+        }
+    }
+}
+
+/// Fresnel reflectance of a conductor with complex index `eta + i k`
+pub fn fresnel_conductor(cos_theta_i: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_theta_i.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+    let t0 = eta * eta - k * k - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos_theta_i.clamp(0.0, 1.0) * a;
+    let rs = (t1 - t2) / (t1 + t2);
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rp + rs)
+}
+
+/// Exact Fresnel reflectance of a dielectric interface, `eta` being n_t / n_i
+pub fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let (cos_theta_i, eta) = if cos_theta_i < 0.0 {
+        (-cos_theta_i, 1.0 / eta)
+    } else {
+        (cos_theta_i.min(1.0), eta)
+    };
+    let sin2_theta_t = (1.0 - cos_theta_i * cos_theta_i) / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    let r_parl = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perp = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    (r_parl * r_parl + r_perp * r_perp) / 2.0
+}
+
+/// Refracts `wo` (pointing away from the surface, on the side of `wm`) through the
+/// microfacet `wm`, `eta` being n_t / n_i. None on total internal reflection.
+pub fn refract(wo: &Vec3, wm: &Vec3, eta: f64) -> Option<Vec3> {
+    let cos_theta_i = wo.dot(wm);
+    let sin2_theta_t = (1.0 - cos_theta_i * cos_theta_i).max(0.0) / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        return None;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    Some(-*wo / eta + (cos_theta_i / eta - cos_theta_t) * *wm)
+}
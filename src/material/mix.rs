@@ -0,0 +1,75 @@
+use super::{Material, MaterialEnum, Param, ScatterRecord};
+use crate::random;
+use crate::{hittable, ray, vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Blend of two materials by a weight, e.g. a mask texture for rust over paint. Each
+/// sample takes one of the two at random, with the weight as the chance of the second,
+/// and is weighted against the blend of both (one-sample MIS).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Mix {
+    first: MaterialEnum,
+    second: MaterialEnum,
+    weight: Param,
+}
+
+impl Mix {
+    /// `weight` is the amount of `second`, from 0 to 1
+    pub fn new(first: MaterialEnum, second: MaterialEnum, weight: impl Into<Param>) -> Self {
+        Self {
+            first,
+            second,
+            weight: weight.into(),
+        }
+    }
+
+    pub fn first(&self) -> &MaterialEnum {
+        &self.first
+    }
+
+    pub fn second(&self) -> &MaterialEnum {
+        &self.second
+    }
+
+    fn weight(&self, rec: &hittable::HitRecord) -> f64 {
+        self.weight.value(rec.u, rec.v, &rec.p).clamp(0.0, 1.0)
+    }
+}
+
+impl Material for Mix {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        let material = if random::rng().gen_range(0.0..1.0) < self.weight(rec) {
+            &self.second
+        } else {
+            &self.first
+        };
+        if !material.sample(r_in, rec, srec) {
+            return false;
+        }
+        // A delta lobe belongs to the material picked alone, and the chance of picking it
+        // cancels out with its weight in the blend
+        if srec.is_delta {
+            return true;
+        }
+
+        let wi = srec.scattered.direction;
+        let pdf = self.pdf(r_in, rec, &wi);
+        if pdf <= 0.0 {
+            return false;
+        }
+        srec.attenuation = self.eval(r_in, rec, &wi) / pdf;
+        srec.pdf = pdf;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        let weight = self.weight(rec);
+        (1.0 - weight) * self.first.eval(r_in, rec, wi) + weight * self.second.eval(r_in, rec, wi)
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        let weight = self.weight(rec);
+        (1.0 - weight) * self.first.pdf(r_in, rec, wi) + weight * self.second.pdf(r_in, rec, wi)
+    }
+}
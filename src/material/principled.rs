@@ -0,0 +1,315 @@
+use super::microfacet::TrowbridgeReitz;
+use super::{reflect, Dielectric, Material, RoughDielectric, ScatterRecord};
+use crate::onb::Onb;
+use crate::random;
+use crate::spectrum::luminance;
+use crate::texture::TextureEnum;
+use crate::vec3::{Color, Point3, Vec3};
+use crate::{hittable, ray};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// A scalar material parameter, either constant or read from the first channel of a texture
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Param {
+    Constant(f64),
+    Texture(TextureEnum),
+}
+
+impl Param {
+    pub fn value(&self, u: f64, v: f64, p: &Point3) -> f64 {
+        match self {
+            Param::Constant(c) => *c,
+            Param::Texture(t) => t.value(u, v, p).x(),
+        }
+    }
+}
+
+impl From<f64> for Param {
+    fn from(c: f64) -> Self {
+        Param::Constant(c)
+    }
+}
+
+impl From<TextureEnum> for Param {
+    fn from(t: TextureEnum) -> Self {
+        Param::Texture(t)
+    }
+}
+
+/// Disney "principled" BSDF: a diffuse base with sheen, a GGX specular lobe,
+/// a clearcoat layer and rough dielectric transmission, blended by a few intuitive parameters
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Principled {
+    base_color: TextureEnum,
+    metallic: Param,
+    roughness: Param,
+    specular: Param,
+    sheen: Param,
+    sheen_tint: Param,
+    clearcoat: Param,
+    clearcoat_gloss: Param,
+    transmission: Param,
+    ior: f64,
+}
+
+/// The parameters evaluated at a hit point
+struct Lobes {
+    base_color: Color,
+    metallic: f64,
+    roughness: f64,
+    specular_f0: Color,
+    sheen: Color,
+    clearcoat: f64,
+    specular: TrowbridgeReitz,
+    coat: TrowbridgeReitz,
+}
+
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5)
+}
+
+fn schlick_fresnel(f0: &Color, cos_theta: f64) -> Color {
+    *f0 + (Color::new(1.0, 1.0, 1.0) - *f0) * schlick_weight(cos_theta)
+}
+
+fn lerp(a: &Color, b: &Color, t: f64) -> Color {
+    (1.0 - t) * *a + t * *b
+}
+
+impl Principled {
+    pub fn new(base_color: &TextureEnum) -> Self {
+        Self {
+            base_color: base_color.clone(),
+            metallic: Param::Constant(0.0),
+            roughness: Param::Constant(0.5),
+            specular: Param::Constant(0.5),
+            sheen: Param::Constant(0.0),
+            sheen_tint: Param::Constant(0.5),
+            clearcoat: Param::Constant(0.0),
+            clearcoat_gloss: Param::Constant(1.0),
+            transmission: Param::Constant(0.0),
+            ior: 1.5,
+        }
+    }
+
+    pub fn with_metallic(mut self, metallic: impl Into<Param>) -> Self {
+        self.metallic = metallic.into();
+        self
+    }
+
+    pub fn with_roughness(mut self, roughness: impl Into<Param>) -> Self {
+        self.roughness = roughness.into();
+        self
+    }
+
+    /// Dielectric reflectance at normal incidence, 0.5 meaning 4%
+    pub fn with_specular(mut self, specular: impl Into<Param>) -> Self {
+        self.specular = specular.into();
+        self
+    }
+
+    pub fn with_sheen(mut self, sheen: impl Into<Param>, sheen_tint: impl Into<Param>) -> Self {
+        self.sheen = sheen.into();
+        self.sheen_tint = sheen_tint.into();
+        self
+    }
+
+    pub fn with_clearcoat(mut self, clearcoat: impl Into<Param>, gloss: impl Into<Param>) -> Self {
+        self.clearcoat = clearcoat.into();
+        self.clearcoat_gloss = gloss.into();
+        self
+    }
+
+    pub fn with_transmission(mut self, transmission: impl Into<Param>, ior: f64) -> Self {
+        self.transmission = transmission.into();
+        self.ior = ior;
+        self
+    }
+
+    fn lobes(&self, u: f64, v: f64, p: &Point3) -> Lobes {
+        let base_color = self.base_color.value(u, v, p);
+        let metallic = self.metallic.value(u, v, p).clamp(0.0, 1.0);
+        let roughness = self.roughness.value(u, v, p).clamp(0.0, 1.0);
+        let specular = self.specular.value(u, v, p).max(0.0);
+
+        let white = Color::new(1.0, 1.0, 1.0);
+        let lum = luminance(&base_color);
+        let tint = if lum > 0.0 { base_color / lum } else { white };
+        let sheen_color = lerp(&white, &tint, self.sheen_tint.value(u, v, p));
+        let dielectric_f0 = 0.08 * specular * white;
+        let gloss = self.clearcoat_gloss.value(u, v, p).clamp(0.0, 1.0);
+        let coat_alpha = (1.0 - gloss) * 0.1 + gloss * 0.001;
+
+        Lobes {
+            base_color,
+            metallic,
+            roughness,
+            specular_f0: lerp(&dielectric_f0, &base_color, metallic),
+            sheen: self.sheen.value(u, v, p).max(0.0) * sheen_color,
+            clearcoat: self.clearcoat.value(u, v, p).clamp(0.0, 1.0),
+            specular: TrowbridgeReitz::from_roughness(roughness, roughness),
+            coat: TrowbridgeReitz::new(coat_alpha, coat_alpha),
+        }
+    }
+
+    /// Probabilities of sampling the diffuse, specular and clearcoat lobes
+    fn lobe_probabilities(lobes: &Lobes) -> (f64, f64, f64) {
+        let diffuse = (1.0 - lobes.metallic) * luminance(&lobes.base_color).max(0.05);
+        let specular = luminance(&lobes.specular_f0).max(0.05) + lobes.metallic;
+        let coat = 0.25 * lobes.clearcoat;
+        let total = diffuse + specular + coat;
+        (diffuse / total, specular / total, coat / total)
+    }
+
+    /// BSDF of the reflective lobes times the cosine of `wi`, in the local frame
+    fn eval_reflection(lobes: &Lobes, wo: &Vec3, wi: &Vec3) -> Color {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Color::zero();
+        }
+        let wh = (*wo + *wi).unit();
+        let cos_d = wi.dot(&wh);
+
+        let fd90 = 0.5 + 2.0 * lobes.roughness * cos_d * cos_d;
+        let fd = |cos_theta: f64| 1.0 + (fd90 - 1.0) * schlick_weight(cos_theta);
+        let diffuse = (1.0 - lobes.metallic)
+            * (lobes.base_color / PI * fd(wo.z()) * fd(wi.z())
+                + lobes.sheen * schlick_weight(cos_d))
+            * wi.z();
+
+        let specular = schlick_fresnel(&lobes.specular_f0, cos_d)
+            * (lobes.specular.d(&wh) * lobes.specular.g(wo, wi) / (4.0 * wo.z()));
+
+        let coat_fresnel = 0.04 + 0.96 * schlick_weight(cos_d);
+        let coat = 0.25 * lobes.clearcoat * coat_fresnel * lobes.coat.d(&wh) * lobes.coat.g(wo, wi)
+            / (4.0 * wo.z());
+
+        diffuse + specular + coat * Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn pdf_reflection(lobes: &Lobes, wo: &Vec3, wi: &Vec3) -> f64 {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let wh = (*wo + *wi).unit();
+        let (p_diffuse, p_specular, p_coat) = Self::lobe_probabilities(lobes);
+        let reflection_jacobian = 1.0 / (4.0 * wo.dot(&wh).abs());
+        p_diffuse * wi.z() / PI
+            + p_specular * lobes.specular.d_visible(wo, &wh) * reflection_jacobian
+            + p_coat * lobes.coat.d_visible(wo, &wh) * reflection_jacobian
+    }
+
+    fn sample_reflection(lobes: &Lobes, wo: &Vec3) -> Vec3 {
+        let mut rng = random::rng();
+        let (p_diffuse, p_specular, _) = Self::lobe_probabilities(lobes);
+        let choice = rng.gen_range(0.0..1.0);
+        if choice < p_diffuse {
+            // Cosine-weighted hemisphere
+            let r1: f64 = rng.gen_range(0.0..1.0);
+            let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+            return Vec3::new(
+                phi.cos() * r1.sqrt(),
+                phi.sin() * r1.sqrt(),
+                (1.0 - r1).sqrt(),
+            );
+        }
+        let distribution = if choice < p_diffuse + p_specular {
+            &lobes.specular
+        } else {
+            &lobes.coat
+        };
+        let wm = distribution.sample_wm(wo, rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+        reflect(&(-*wo), &wm)
+    }
+
+    pub(super) fn transmission_lobe(&self, u: f64, v: f64, p: &Point3) -> RoughDielectric {
+        RoughDielectric::new(Dielectric::new(self.ior), self.roughness.value(u, v, p))
+    }
+
+    /// Share of the transmissive lobe, which metals don't have
+    fn transmission_weight(&self, u: f64, v: f64, p: &Point3) -> f64 {
+        (1.0 - self.metallic.value(u, v, p).clamp(0.0, 1.0))
+            * self.transmission.value(u, v, p).clamp(0.0, 1.0)
+    }
+
+    /// Whether some light can go through the surface, anywhere on it
+    pub(super) fn has_transmission(&self) -> bool {
+        !matches!(self.transmission, Param::Constant(t) if t <= 0.0)
+            && !matches!(self.metallic, Param::Constant(m) if m >= 1.0)
+    }
+
+    /// Whether `rec` is on the inside of a transmissive object, where only the refractive
+    /// interface remains. Opaque surfaces seen from behind reflect as from the front.
+    fn inside(rec: &hittable::HitRecord, transmission: f64) -> bool {
+        !rec.front_face && transmission > 0.0
+    }
+
+    /// f cos and pdf of the whole BSDF, delta lobes excluded
+    fn eval_pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &Vec3) -> (Color, f64) {
+        let transmitting = self.transmission_lobe(rec.u, rec.v, &rec.p);
+        let transmission = self.transmission_weight(rec.u, rec.v, &rec.p);
+        if Self::inside(rec, transmission) {
+            return (
+                transmitting.eval(r_in, rec, wi),
+                transmitting.pdf(r_in, rec, wi),
+            );
+        }
+
+        let lobes = self.lobes(rec.u, rec.v, &rec.p);
+        let uvw = Onb::build_from_w(&rec.normal);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        let wi_local = uvw.to_local(&wi.unit());
+        let f = (1.0 - transmission) * Self::eval_reflection(&lobes, &wo, &wi_local)
+            + transmission * lobes.base_color * transmitting.eval(r_in, rec, wi);
+        let pdf = (1.0 - transmission) * Self::pdf_reflection(&lobes, &wo, &wi_local)
+            + transmission * transmitting.pdf(r_in, rec, wi);
+        (f, pdf)
+    }
+}
+
+impl Material for Principled {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        let transmission = self.transmission_weight(rec.u, rec.v, &rec.p);
+
+        // Inside the object only the refractive interface remains. Otherwise a lobe is
+        // picked at random and weighted against the whole BSDF (one-sample MIS).
+        let inside = Self::inside(rec, transmission);
+        if inside || random::rng().gen_range(0.0..1.0) < transmission {
+            let transmitting = self.transmission_lobe(rec.u, rec.v, &rec.p);
+            if !transmitting.sample(r_in, rec, srec) {
+                return false;
+            }
+            if inside {
+                return true;
+            }
+            if srec.is_delta {
+                srec.attenuation = srec.attenuation * self.base_color.value(rec.u, rec.v, &rec.p);
+                return true;
+            }
+        } else {
+            let lobes = self.lobes(rec.u, rec.v, &rec.p);
+            let uvw = Onb::build_from_w(&rec.normal);
+            let wo = uvw.to_local(&(-r_in.direction.unit()));
+            let wi = Self::sample_reflection(&lobes, &wo);
+            srec.scattered = r_in.spawn(&rec.p, &uvw.local(&wi));
+            srec.is_delta = false;
+        }
+
+        let (f, pdf) = self.eval_pdf(r_in, rec, &srec.scattered.direction);
+        if pdf <= 0.0 {
+            return false;
+        }
+        srec.attenuation = f / pdf;
+        srec.pdf = pdf;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &Vec3) -> Color {
+        self.eval_pdf(r_in, rec, wi).0
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &Vec3) -> f64 {
+        self.eval_pdf(r_in, rec, wi).1
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Wavelength used when a ray does not carry one (sodium D line, in nm)
+const D_LINE: f64 = 587.6;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RefractiveIndex {
+    Constant(f64),
+    /// n = a + b / λ², with λ in µm
+    Cauchy {
+        a: f64,
+        b: f64,
+    },
+    /// n² = 1 + Σ b_i λ² / (λ² - c_i), with λ in µm
+    Sellmeier {
+        b: [f64; 3],
+        c: [f64; 3],
+    },
+}
+
+impl RefractiveIndex {
+    /// Index of refraction at `wavelength` in nm
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        let l = wavelength.unwrap_or(D_LINE) * 1e-3;
+        let l2 = l * l;
+        match self {
+            RefractiveIndex::Constant(n) => *n,
+            RefractiveIndex::Cauchy { a, b } => a + b / l2,
+            RefractiveIndex::Sellmeier { b, c } => {
+                let mut n2 = 1.0;
+                for i in 0..3 {
+                    n2 += b[i] * l2 / (l2 - c[i]);
+                }
+                n2.sqrt()
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, RefractiveIndex::Constant(_))
+    }
+}
@@ -0,0 +1,177 @@
+use super::microfacet::{self, fresnel_dielectric, TrowbridgeReitz};
+use super::{reflect, Dielectric, InteriorEntry, Material, ScatterRecord};
+use crate::onb::Onb;
+use crate::random;
+use crate::texture::{SolidColor, TextureEnum};
+use crate::{hittable, ray, vec3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Frosted glass: a `Dielectric` volume whose surface is a GGX microfacet interface
+/// (Walter et al., "Microfacet Models for Refraction through Rough Surfaces")
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoughDielectric {
+    base: Dielectric,
+    roughness: TextureEnum,
+}
+
+impl RoughDielectric {
+    /// `base` describes the volume, `roughness` is perceptual, in [0, 1]
+    pub fn new(base: Dielectric, roughness: f64) -> Self {
+        Self::with_roughness_texture(
+            base,
+            TextureEnum::SolidColor(SolidColor::new(vec3::Color::new(
+                roughness, roughness, roughness,
+            ))),
+        )
+    }
+
+    /// Roughness is read from the first channel of `roughness`
+    pub fn with_roughness_texture(base: Dielectric, roughness: TextureEnum) -> Self {
+        Self { base, roughness }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+
+    pub(super) fn interior_entry(&self) -> InteriorEntry {
+        self.base.interior_entry()
+    }
+
+    fn distribution(&self, rec: &hittable::HitRecord) -> TrowbridgeReitz {
+        let roughness = self.roughness.value(rec.u, rec.v, &rec.p).x();
+        TrowbridgeReitz::from_roughness(roughness, roughness)
+    }
+
+    /// f cos and pdf towards `wi`, or None where the surface acts as a delta lobe
+    fn eval_pdf(
+        &self,
+        r_in: &ray::Ray,
+        rec: &hittable::HitRecord,
+        wi: &vec3::Vec3,
+    ) -> Option<(f64, f64)> {
+        let distribution = self.distribution(rec);
+        if distribution.effectively_smooth() {
+            return None;
+        }
+        let crossing =
+            r_in.interior
+                .cross_at(&self.base.interior_entry(), rec.front_face, r_in.wavelength);
+        if crossing.hidden {
+            return None;
+        }
+
+        let uvw = Onb::build_from_w(&rec.normal);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        let wi = uvw.to_local(&wi.unit());
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return Some((0.0, 0.0));
+        }
+        let eta = 1.0 / crossing.etai_over_etat;
+
+        if wi.z() > 0.0 {
+            let wm = (wo + wi).unit();
+            let reflectance = fresnel_dielectric(wo.dot(&wm), eta);
+            let f_cos =
+                distribution.d(&wm) * distribution.g(&wo, &wi) * reflectance / (4.0 * wo.z());
+            let pdf = distribution.d_visible(&wo, &wm) * reflectance / (4.0 * wo.dot(&wm).abs());
+            return Some((f_cos, pdf));
+        }
+
+        // Generalized half vector of the refraction, facing the incident side
+        let wm = (wo + eta * wi).unit();
+        let wm = if wm.z() < 0.0 { -wm } else { wm };
+        if wo.dot(&wm) <= 0.0 || wi.dot(&wm) >= 0.0 {
+            return Some((0.0, 0.0));
+        }
+        let transmittance = 1.0 - fresnel_dielectric(wo.dot(&wm), eta);
+        let denom = (wi.dot(&wm) + wo.dot(&wm) / eta).powi(2);
+        let f_cos = distribution.d(&wm)
+            * distribution.g(&wo, &wi)
+            * transmittance
+            * (wi.dot(&wm) * wo.dot(&wm)).abs()
+            / (wo.z() * denom);
+        let pdf = distribution.d_visible(&wo, &wm) * transmittance * wi.dot(&wm).abs() / denom;
+        Some((f_cos, pdf))
+    }
+}
+
+impl Material for RoughDielectric {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        let distribution = self.distribution(rec);
+        if distribution.effectively_smooth() {
+            return self.base.sample(r_in, rec, srec);
+        }
+
+        let crossing =
+            r_in.interior
+                .cross(&self.base.interior_entry(), rec.front_face, r_in.wavelength);
+        if crossing.hidden {
+            srec.attenuation = vec3::Color::new(1.0, 1.0, 1.0);
+            srec.scattered = r_in.spawn(&rec.p, &r_in.direction);
+            srec.scattered.interior = crossing.inside;
+            srec.pdf = 0.0;
+            srec.is_delta = true;
+            return true;
+        }
+
+        let uvw = Onb::build_from_w(&rec.normal);
+        let wo = uvw.to_local(&(-r_in.direction.unit()));
+        if wo.z() <= 0.0 {
+            return false;
+        }
+        let eta = 1.0 / crossing.etai_over_etat;
+        let mut rng = random::rng();
+        let wm = distribution.sample_wm(&wo, rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+
+        // Choose reflection or transmission by the Fresnel term, so that
+        // f cos / pdf reduces to G2 / G1 either way
+        let reflectance = fresnel_dielectric(wo.dot(&wm), eta);
+        let transmitted = if rng.gen_range(0.0..1.0) < reflectance {
+            None
+        } else {
+            microfacet::refract(&wo, &wm, eta)
+        };
+        let (wi, pdf) = match transmitted {
+            Some(wi) if wi.z() < 0.0 => {
+                let denom = (wi.dot(&wm) + wo.dot(&wm) / eta).powi(2);
+                let pdf =
+                    distribution.d_visible(&wo, &wm) * (1.0 - reflectance) * wi.dot(&wm).abs()
+                        / denom;
+                (wi, pdf)
+            }
+            Some(_) => return false,
+            None => {
+                let wi = reflect(&(-wo), &wm);
+                if wi.z() <= 0.0 {
+                    return false;
+                }
+                let pdf =
+                    distribution.d_visible(&wo, &wm) * reflectance / (4.0 * wo.dot(&wm).abs());
+                (wi, pdf)
+            }
+        };
+
+        srec.attenuation = crossing.weight * (distribution.g(&wo, &wi) / distribution.g1(&wo));
+        srec.scattered = r_in.spawn(&rec.p, &uvw.local(&wi));
+        srec.scattered.wavelength = crossing.wavelength;
+        if transmitted.is_some() {
+            srec.scattered.interior = crossing.inside;
+        }
+        srec.pdf = pdf;
+        srec.is_delta = false;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        match self.eval_pdf(r_in, rec, wi) {
+            Some((f_cos, _)) => vec3::Color::new(f_cos, f_cos, f_cos),
+            None => vec3::Color::zero(),
+        }
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        self.eval_pdf(r_in, rec, wi).map_or(0.0, |(_, pdf)| pdf)
+    }
+}
@@ -0,0 +1,94 @@
+use super::{InteriorEntry, Material, RefractiveIndex, ScatterRecord};
+use crate::{hittable, ray, vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Translucent material for skin, marble, wax or milk. Light goes into the closed object
+/// and random-walks through the volume inside, scattering isotropically, until it
+/// reaches the surface again and leaves. The surface lets light through in a cosine
+/// distribution either way, which light samples can find the exit points by.
+///
+/// Every scattering event counts as a bounce of the path, so mean free paths much
+/// shorter than the object need a deep maximum depth.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Subsurface {
+    priority: u32,
+    /// Absorption and scattering coefficients per unit length for each color channel
+    absorption: vec3::Color,
+    scattering: vec3::Color,
+}
+
+impl Subsurface {
+    /// For each color channel, `albedo` is the fraction of the light kept at every
+    /// scattering event inside and `mean_free_path` the average distance between them
+    pub fn new(albedo: &vec3::Color, mean_free_path: &vec3::Color) -> Self {
+        let extinction = vec3::Color::new(
+            1.0 / mean_free_path.x(),
+            1.0 / mean_free_path.y(),
+            1.0 / mean_free_path.z(),
+        );
+        let albedo = vec3::Color::new(
+            albedo.x().clamp(0.0, 1.0),
+            albedo.y().clamp(0.0, 1.0),
+            albedo.z().clamp(0.0, 1.0),
+        );
+        Self {
+            priority: 0,
+            absorption: extinction * (vec3::Color::new(1.0, 1.0, 1.0) - albedo),
+            scattering: extinction * albedo,
+        }
+    }
+
+    /// See `Dielectric::with_priority`
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub(super) fn interior_entry(&self) -> InteriorEntry {
+        InteriorEntry {
+            ior: RefractiveIndex::Constant(1.0),
+            priority: self.priority,
+            absorption: self.absorption,
+            scattering: self.scattering,
+        }
+    }
+}
+
+impl Material for Subsurface {
+    fn sample(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, srec: &mut ScatterRecord) -> bool {
+        let crossing =
+            r_in.interior
+                .cross_at(&self.interior_entry(), rec.front_face, r_in.wavelength);
+        srec.attenuation = vec3::Color::new(1.0, 1.0, 1.0);
+
+        if crossing.hidden {
+            srec.pdf = 0.0;
+            srec.is_delta = true;
+            srec.scattered = r_in.spawn(&rec.p, &r_in.direction);
+            srec.scattered.interior = crossing.inside;
+            return true;
+        }
+
+        let direction = -rec.normal + vec3::Vec3::rand_unit_vector();
+        srec.scattered = r_in.spawn(&rec.p, &direction);
+        srec.scattered.interior = crossing.inside;
+        srec.pdf = self.pdf(r_in, rec, &direction.unit());
+        srec.is_delta = false;
+        true
+    }
+
+    fn eval(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> vec3::Color {
+        vec3::Color::new(1.0, 1.0, 1.0) * self.pdf(r_in, rec, wi)
+    }
+
+    fn pdf(&self, r_in: &ray::Ray, rec: &hittable::HitRecord, wi: &vec3::Vec3) -> f64 {
+        let crossing =
+            r_in.interior
+                .cross_at(&self.interior_entry(), rec.front_face, r_in.wavelength);
+        if crossing.hidden {
+            return 0.0;
+        }
+        (-rec.normal.dot(wi)).max(0.0) / PI
+    }
+}
@@ -0,0 +1,144 @@
+use super::Param;
+use crate::hittable;
+use crate::spectrum::reflectance_to_rgb;
+use crate::texture::TextureEnum;
+use crate::vec3::Color;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// A transparent layer a few hundred nanometres thick on a surface, as of soap, oil or
+/// an anti-reflection coating. Light reflected off its top and bottom interferes, so the
+/// reflectance changes with the wavelength, the angle and the thickness.
+///
+/// Paths that carry a single wavelength, as in spectral rendering or after dispersion,
+/// see the reflectance at that wavelength. Others see it integrated over the visible range.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThinFilm {
+    /// Mapped from `min`..`max` to nm: 0..1 for constants, which are in nm, and the
+    /// range given for textures, whose first channel is clamped to [0, 1]
+    thickness: Param,
+    min: f64,
+    max: f64,
+    ior: f64,
+}
+
+/// Amplitude reflection coefficients for s and p polarized light going from index `ni`
+/// to `nt`, at cosines `cos_i` and `cos_t` from the normal
+fn fresnel_amplitudes(ni: f64, cos_i: f64, nt: f64, cos_t: f64) -> (f64, f64) {
+    let s = (ni * cos_i - nt * cos_t) / (ni * cos_i + nt * cos_t);
+    let p = (nt * cos_i - ni * cos_t) / (nt * cos_i + ni * cos_t);
+    (s, p)
+}
+
+/// Reflectance of a film from the reflection coefficients at its top and bottom and the
+/// phase difference between the two, summing all the reflections inside (Airy)
+fn airy(r12: f64, r23: f64, phase: f64) -> f64 {
+    let cross = 2.0 * r12 * r23 * phase.cos();
+    ((r12 * r12 + r23 * r23 + cross) / (1.0 + r12 * r12 * r23 * r23 + cross)).clamp(0.0, 1.0)
+}
+
+impl ThinFilm {
+    /// `thickness` in nm
+    pub fn new(thickness: f64, ior: f64) -> Self {
+        Self {
+            thickness: Param::Constant(thickness),
+            min: 0.0,
+            max: 1.0,
+            ior,
+        }
+    }
+
+    /// Thickness varying from `min` to `max` (nm) with the first channel of `texture`
+    pub fn with_thickness_texture(texture: TextureEnum, min: f64, max: f64, ior: f64) -> Self {
+        Self {
+            thickness: Param::Texture(texture),
+            min,
+            max,
+            ior,
+        }
+    }
+
+    fn thickness(&self, rec: &hittable::HitRecord) -> f64 {
+        let t = match &self.thickness {
+            Param::Constant(thickness) => *thickness,
+            Param::Texture(_) => self.thickness.value(rec.u, rec.v, &rec.p).clamp(0.0, 1.0),
+        };
+        self.min + (self.max - self.min) * t
+    }
+
+    /// Cosine from the normal inside the film of light arriving at `cos_theta` from index
+    /// `outside`, None if the film reflects it all
+    fn cos_in_film(&self, cos_theta: f64, outside: f64) -> Option<f64> {
+        let sin2 = (outside / self.ior).powi(2) * (1.0 - cos_theta * cos_theta);
+        (sin2 < 1.0).then(|| (1.0 - sin2).sqrt())
+    }
+
+    /// Phase difference between light reflected off the bottom and the top of the film
+    fn phase(thickness: f64, ior: f64, cos_film: f64, lambda: f64) -> f64 {
+        4.0 * PI * ior * thickness * cos_film / lambda
+    }
+
+    /// At `wavelength` if there is one, as RGB otherwise
+    fn resolve(wavelength: Option<f64>, f: impl Fn(f64) -> f64) -> Color {
+        match wavelength {
+            Some(lambda) => Color::new(1.0, 1.0, 1.0) * f(lambda),
+            None => reflectance_to_rgb(f),
+        }
+    }
+
+    /// Reflectance of the film over a dielectric, between indices `outside` and `inside`,
+    /// for light arriving at `cos_theta`
+    pub(super) fn reflectance_dielectric(
+        &self,
+        rec: &hittable::HitRecord,
+        wavelength: Option<f64>,
+        cos_theta: f64,
+        outside: f64,
+        inside: f64,
+    ) -> Color {
+        let cos_film = match self.cos_in_film(cos_theta, outside) {
+            Some(cos_film) => cos_film,
+            None => return Color::new(1.0, 1.0, 1.0),
+        };
+        let sin2_inside = (outside / inside).powi(2) * (1.0 - cos_theta * cos_theta);
+        if sin2_inside >= 1.0 {
+            return Color::new(1.0, 1.0, 1.0);
+        }
+        let cos_inside = (1.0 - sin2_inside).sqrt();
+
+        let (s12, p12) = fresnel_amplitudes(outside, cos_theta, self.ior, cos_film);
+        let (s23, p23) = fresnel_amplitudes(self.ior, cos_film, inside, cos_inside);
+        let thickness = self.thickness(rec);
+        Self::resolve(wavelength, |lambda| {
+            let phase = Self::phase(thickness, self.ior, cos_film, lambda);
+            0.5 * (airy(s12, s23, phase) + airy(p12, p23, phase))
+        })
+    }
+
+    /// Reflectance of the film over a metal of reflectance `albedo`, taken to shift the
+    /// phase of the light it reflects by half a wave, for light arriving at `cos_theta`
+    /// from index `outside`
+    pub(super) fn reflectance_metal(
+        &self,
+        rec: &hittable::HitRecord,
+        wavelength: Option<f64>,
+        cos_theta: f64,
+        outside: f64,
+        albedo: &Color,
+    ) -> Color {
+        let cos_film = match self.cos_in_film(cos_theta, outside) {
+            Some(cos_film) => cos_film,
+            None => return Color::new(1.0, 1.0, 1.0),
+        };
+        let (s12, p12) = fresnel_amplitudes(outside, cos_theta, self.ior, cos_film);
+        let thickness = self.thickness(rec);
+        let channel = |c: usize| {
+            let r23 = -albedo[c].clamp(0.0, 1.0).sqrt();
+            Self::resolve(wavelength, |lambda| {
+                let phase = Self::phase(thickness, self.ior, cos_film, lambda);
+                0.5 * (airy(s12, r23, phase) + airy(p12, r23, phase))
+            })[c]
+        };
+        Color::new(channel(0), channel(1), channel(2))
+    }
+}
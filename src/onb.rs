@@ -0,0 +1,46 @@
+use crate::vec3::Vec3;
+
+/// Orthonormal basis with `w` along a surface normal
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn build_from_w(n: &Vec3) -> Self {
+        let w = n.unit();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).unit();
+        let u = w.cross(&v);
+        Self { u, v, w }
+    }
+
+    /// Basis with `u` along the part of `tangent` that is perpendicular to `n`, or any
+    /// basis around `n` if there is none
+    pub fn build_from_w_u(n: &Vec3, tangent: &Vec3) -> Self {
+        let w = n.unit();
+        let u = *tangent - tangent.dot(&w) * w;
+        if u.power() < 1e-12 * tangent.power().max(1e-12) {
+            return Self::build_from_w(n);
+        }
+        let u = u.unit();
+        let v = w.cross(&u);
+        Self { u, v, w }
+    }
+
+    /// Local coordinates to world space
+    pub fn local(&self, a: &Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+
+    /// World space to local coordinates
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
@@ -0,0 +1,77 @@
+use rand::RngCore;
+use std::cell::RefCell;
+
+/// Uniform numbers in [0, 1), drawn in turn by `rng` while the source is set, so that
+/// a path can be traced again from the same numbers or from slightly changed ones
+pub trait SampleSource {
+    fn next_sample(&mut self) -> f64;
+}
+
+thread_local! {
+    static SOURCE: RefCell<Option<Box<dyn SampleSource>>> = const { RefCell::new(None) };
+}
+
+/// Random numbers for rendering: from the source set on this thread by `with_source`,
+/// if any, and from `rand::thread_rng` otherwise
+pub fn rng() -> SampleRng {
+    SampleRng
+}
+
+/// Calls `f` with the random numbers drawn through `rng` taken from `source`
+pub fn with_source<R>(source: impl SampleSource + 'static, f: impl FnOnce() -> R) -> R {
+    // Restores the previous source, even if `f` panics
+    struct Restore(Option<Box<dyn SampleSource>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SOURCE.with(|source| *source.borrow_mut() = previous);
+        }
+    }
+
+    let previous = SOURCE.with(|current| current.borrow_mut().replace(Box::new(source)));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// See `rng`
+#[derive(Debug, Clone, Copy)]
+pub struct SampleRng;
+
+impl SampleRng {
+    fn next_sample(&self) -> Option<f64> {
+        SOURCE.with(|source| {
+            source
+                .borrow_mut()
+                .as_mut()
+                .map(|source| source.next_sample())
+        })
+    }
+}
+
+impl RngCore for SampleRng {
+    fn next_u32(&mut self) -> u32 {
+        match self.next_sample() {
+            Some(u) => (u * 2f64.powi(32)) as u32,
+            None => rand::thread_rng().next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self.next_sample() {
+            Some(u) => (u * 2f64.powi(64)) as u64,
+            None => rand::thread_rng().next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
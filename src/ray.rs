@@ -1,10 +1,22 @@
+use crate::background::Background;
 use crate::hittable::{self, Hittable};
+use crate::integrator::{power_heuristic, LightSampling, PathSettings, PathStats, Termination};
+use crate::light::{Light, LightEnum, LightSampler};
+use crate::material::{InteriorStack, Isotropic, MaterialEnum, ScatterRecord};
+use crate::random;
+use crate::spectrum::{SampledSpectrum, SampledWavelengths};
+use crate::texture::{SolidColor, TextureEnum};
 use crate::vec3;
+use rand::Rng;
 
+#[derive(Debug, Clone)]
 pub struct Ray {
     pub origin: vec3::Point3,
     pub direction: vec3::Vec3,
     pub time: f64,
+    pub interior: InteriorStack,
+    /// Set once the path has been narrowed down to a single wavelength (in nm)
+    pub wavelength: Option<f64>,
 }
 
 impl Ray {
@@ -13,6 +25,19 @@ impl Ray {
             origin: *origin,
             direction: *direction,
             time,
+            interior: InteriorStack::new(),
+            wavelength: None,
+        }
+    }
+
+    /// A ray leaving a surface, keeping the time, interior and wavelength of this ray
+    pub fn spawn(&self, origin: &vec3::Point3, direction: &vec3::Vec3) -> Self {
+        Ray {
+            origin: *origin,
+            direction: *direction,
+            time: self.time,
+            interior: self.interior.clone(),
+            wavelength: self.wavelength,
         }
     }
 
@@ -20,35 +45,433 @@ impl Ray {
         self.origin + self.direction * t
     }
 
+    /// Interior of a ray leaving `rec` towards `direction`, which lies across the surface
+    /// if it goes through it
+    pub fn interior_towards(
+        &self,
+        rec: &hittable::HitRecord,
+        mat: &MaterialEnum,
+        direction: &vec3::Vec3,
+    ) -> InteriorStack {
+        if direction.dot(&rec.normal) < 0.0 {
+            mat.interior_across(self, rec)
+        } else {
+            self.interior.clone()
+        }
+    }
+
+    /// Traces a path starting with this ray and returns the radiance arriving along it.
+    /// `lights` should hold every emitter of `world` (see `light::collect_lights`)
+    /// and is only used by `LightSampling::Nee` and `LightSampling::Mis`, without which
+    /// delta lights are never seen.
     pub fn color(
         &self,
-        background: &vec3::Color,
+        sampling: LightSampling,
+        background: &Background,
         world: &hittable::HittableEnum,
-        depth: usize,
+        lights: &LightSampler,
+        settings: &PathSettings,
+    ) -> (vec3::Color, PathStats) {
+        let mut rng = random::rng();
+        let mut radiance = vec3::Color::zero();
+        let mut throughput = vec3::Color::new(1.0, 1.0, 1.0);
+        let mut ray = self.clone();
+        // Density of the last BSDF sample, None after a specular bounce or for camera rays
+        let mut bsdf_pdf: Option<f64> = None;
+        // Normal the lights were picked for at the origin of `ray`
+        let mut light_normal = vec3::Vec3::zero();
+
+        for bounce in 0..settings.max_depth {
+            let mut rec = hittable::HitRecord::new();
+            let hit = world.hit(&ray, 0.001, f64::INFINITY, &mut rec);
+            // Glowing media the ray goes through up to where it stops
+            radiance += throughput * ray.glow(world, hit, &rec);
+            if !hit {
+                // Only the directions the lights can sample were covered by light samples
+                let weight = match (sampling, bsdf_pdf) {
+                    (LightSampling::Nee, Some(_))
+                        if lights.pdf(&ray.origin, &light_normal, &ray.direction) > 0.0 =>
+                    {
+                        0.0
+                    }
+                    (LightSampling::Mis, Some(pdf)) => {
+                        power_heuristic(pdf, lights.pdf(&ray.origin, &light_normal, &ray.direction))
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput * background.value(&ray.direction) * weight;
+                return (radiance, PathStats::new(bounce, Termination::Escaped));
+            }
+
+            // Absorption by the volume the ray travelled through to reach the hit, or
+            // scattering in it on the way
+            throughput = throughput * ray.through_interior(&mut rec);
+
+            let mat = rec.clone().material;
+            if mat.is_none() {
+                panic!("Material is None");
+            }
+            let mat = mat.unwrap();
+
+            if mat.is_emissive() {
+                let emitted =
+                    mat.emitted(rec.u, rec.v, &rec.p) * mat.emission_falloff(&rec, &-ray.direction);
+                // Emitters found by BSDF sampling after a non-specular bounce were already
+                // found by the light sample of that bounce, entirely or in part
+                let weight = match (sampling, bsdf_pdf) {
+                    (LightSampling::Nee, Some(_)) => 0.0,
+                    (LightSampling::Mis, Some(pdf)) => {
+                        power_heuristic(pdf, lights.pdf(&ray.origin, &light_normal, &ray.direction))
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput * emitted * weight;
+            }
+
+            let mut srec = ScatterRecord::new();
+            if !mat.sample(&ray, &rec, &mut srec) {
+                return (radiance, PathStats::new(bounce + 1, Termination::Absorbed));
+            }
+
+            bsdf_pdf = None;
+            if !srec.is_delta && !lights.is_empty() && sampling != LightSampling::Bsdf {
+                if let Some((weight, incoming)) =
+                    ray.light_sample(sampling, &rec, &mat, background, world, lights)
+                {
+                    radiance += throughput * weight * incoming.rgb();
+                }
+                bsdf_pdf = Some(srec.pdf);
+                light_normal = light_normal_at(&rec, &mat);
+            }
+            throughput = throughput * srec.attenuation;
+
+            // Dim paths are ended at random, and the survivors brightened to make up for it
+            if bounce + 1 >= settings.rr_depth {
+                let survival = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(0.95);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    return (
+                        radiance,
+                        PathStats::new(bounce + 1, Termination::RussianRoulette),
+                    );
+                }
+                throughput /= survival;
+            }
+
+            ray = srec.scattered;
+        }
+
+        (
+            radiance,
+            PathStats::new(settings.max_depth, Termination::MaxDepth),
+        )
+    }
+
+    /// Weight of reaching the hit `rec` through the interior of this ray. Scattering
+    /// interiors may stop the ray on the way, making `rec` the point where it scatters,
+    /// with an isotropic phase function.
+    pub fn through_interior(&self, rec: &mut hittable::HitRecord) -> vec3::Color {
+        let length = self.direction.len();
+        let (scattered, weight) = self.interior.sample_scattering(rec.t * length);
+        if let Some(distance) = scattered {
+            rec.t = distance / length;
+            rec.p = self.at(rec.t);
+            rec.normal = vec3::Vec3::new(1.0, 0.0, 0.0); // this is arbitrary
+            rec.dpdu = vec3::Vec3::zero();
+            rec.front_face = true;
+            rec.material = Some(MaterialEnum::Isotropic(Isotropic::new(
+                &TextureEnum::SolidColor(SolidColor::new(vec3::Color::new(1.0, 1.0, 1.0))),
+            )));
+        }
+        weight
+    }
+
+    /// Light from glowing media along this ray, up to `rec` if it hit something. The
+    /// transmittance of the media is left out, as the chance that the hit was found
+    /// beyond them accounts for it.
+    pub fn glow(
+        &self,
+        world: &hittable::HittableEnum,
+        hit: bool,
+        rec: &hittable::HitRecord,
     ) -> vec3::Color {
-        if depth == 0 {
+        if !world.glows() {
             return vec3::Color::zero();
         }
+        world.emission(self, 0.001, if hit { rec.t } else { f64::INFINITY })
+    }
+
+    /// Light sample of `sampling` at `rec`, as the weight of the sample and the light it
+    /// reaches. Only `LightSampling::Nee` and `LightSampling::Mis` sample lights.
+    fn light_sample(
+        &self,
+        sampling: LightSampling,
+        rec: &hittable::HitRecord,
+        mat: &MaterialEnum,
+        background: &Background,
+        world: &hittable::HittableEnum,
+        lights: &LightSampler,
+    ) -> Option<(vec3::Color, Incoming)> {
+        match sampling {
+            LightSampling::Bsdf => None,
+            LightSampling::Nee => self.sample_light(rec, mat, world, lights),
+            LightSampling::Mis => self.sample_light_mis(rec, mat, background, world, lights),
+        }
+    }
+
+    /// Random point on a random light, reflected at `rec` towards this ray
+    fn sample_light(
+        &self,
+        rec: &hittable::HitRecord,
+        mat: &MaterialEnum,
+        world: &hittable::HittableEnum,
+        lights: &LightSampler,
+    ) -> Option<(vec3::Color, Incoming)> {
+        let (light, pmf) = lights.sample(&rec.p, &light_normal_at(rec, mat))?;
+        let (weight, incoming) = self.sample_one_light(rec, mat, world, light)?;
+        Some((weight / pmf, incoming))
+    }
+
+    /// Random point on `light`, reflected at `rec` towards this ray
+    fn sample_one_light(
+        &self,
+        rec: &hittable::HitRecord,
+        mat: &MaterialEnum,
+        world: &hittable::HittableEnum,
+        light: &LightEnum,
+    ) -> Option<(vec3::Color, Incoming)> {
+        let sample = match light.sample(&rec.p) {
+            Some(sample) if sample.pdf > 0.0 => sample,
+            _ => return None,
+        };
+
+        let mut shadow = self.spawn(&rec.p, &sample.direction);
+        shadow.interior = self.interior_towards(rec, mat, &sample.direction);
+        let mut occluder = hittable::HitRecord::new();
+        if world.hit(
+            &shadow,
+            0.001,
+            sample.distance * (1.0 - 1e-6),
+            &mut occluder,
+        ) {
+            return None;
+        }
+
+        Some((
+            shadow.interior.transmittance(sample.distance.min(f64::MAX))
+                * mat.eval(self, rec, &sample.direction)
+                / sample.pdf,
+            Incoming::Light(sample.radiance),
+        ))
+    }
 
-        let mut rec = hittable::HitRecord::new();
-        if !world.hit(self, 0.001, f64::INFINITY, &mut rec) {
-            return *background;
+    /// Light sample of `LightSampling::Mis`. Whatever emitter the shadow ray reaches first
+    /// counts, so the density is that of the whole light list. Delta lights can only be
+    /// found this way and are taken as they are.
+    fn sample_light_mis(
+        &self,
+        rec: &hittable::HitRecord,
+        mat: &MaterialEnum,
+        background: &Background,
+        world: &hittable::HittableEnum,
+        lights: &LightSampler,
+    ) -> Option<(vec3::Color, Incoming)> {
+        let normal = light_normal_at(rec, mat);
+        let (light, pmf) = lights.sample(&rec.p, &normal)?;
+        if light.is_delta() {
+            let (weight, incoming) = self.sample_one_light(rec, mat, world, light)?;
+            return Some((weight / pmf, incoming));
+        }
+        let direction = light.sample(&rec.p)?.direction;
+        let pdf = lights.pdf(&rec.p, &normal, &direction);
+        if pdf <= 0.0 {
+            return None;
         }
+        let f = mat.eval(self, rec, &direction);
+        if f == vec3::Color::zero() {
+            return None;
+        }
+
+        let mut shadow = self.spawn(&rec.p, &direction);
+        shadow.interior = self.interior_towards(rec, mat, &direction);
+        let mut light_rec = hittable::HitRecord::new();
+        let (incoming, distance) = if world.hit(&shadow, 0.001, f64::INFINITY, &mut light_rec) {
+            match &light_rec.material {
+                Some(m) if m.is_emissive() => {}
+                _ => return None,
+            }
+            let distance = light_rec.t;
+            (Incoming::Surface(Box::new(light_rec), -direction), distance)
+        } else if background.is_light() {
+            (Incoming::Light(background.value(&direction)), f64::MAX)
+        } else {
+            return None;
+        };
 
-        let mat = rec.clone().material;
-        if mat.is_none() {
-            panic!("Material is None");
+        let weight = power_heuristic(pdf, mat.pdf(self, rec, &direction));
+        Some((
+            shadow.interior.transmittance(distance) * f * (weight / pdf),
+            incoming,
+        ))
+    }
+
+    /// Spectral counterpart of `color`, tracing the wavelengths in `lambdas`. The ray
+    /// should carry the hero wavelength of `lambdas`.
+    pub fn color_spectral(
+        &self,
+        sampling: LightSampling,
+        background: &Background,
+        world: &hittable::HittableEnum,
+        lights: &LightSampler,
+        lambdas: &mut SampledWavelengths,
+        settings: &PathSettings,
+    ) -> (SampledSpectrum, PathStats) {
+        let mut rng = random::rng();
+        let mut radiance = SampledSpectrum::zero();
+        let mut throughput = SampledSpectrum::new(1.0);
+        let mut ray = self.clone();
+        let mut bsdf_pdf: Option<f64> = None;
+        let mut light_normal = vec3::Vec3::zero();
+
+        for bounce in 0..settings.max_depth {
+            let mut rec = hittable::HitRecord::new();
+            let hit = world.hit(&ray, 0.001, f64::INFINITY, &mut rec);
+            radiance += throughput
+                * SampledSpectrum::from_rgb_illuminant(&ray.glow(world, hit, &rec), lambdas);
+            if !hit {
+                let weight = match (sampling, bsdf_pdf) {
+                    (LightSampling::Nee, Some(_))
+                        if lights.pdf(&ray.origin, &light_normal, &ray.direction) > 0.0 =>
+                    {
+                        0.0
+                    }
+                    (LightSampling::Mis, Some(pdf)) => {
+                        power_heuristic(pdf, lights.pdf(&ray.origin, &light_normal, &ray.direction))
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput
+                    * SampledSpectrum::from_rgb_illuminant(
+                        &background.value(&ray.direction),
+                        lambdas,
+                    )
+                    * weight;
+                return (radiance, PathStats::new(bounce, Termination::Escaped));
+            }
+
+            throughput *= SampledSpectrum::from_rgb(&ray.through_interior(&mut rec), lambdas);
+
+            let mat = rec.clone().material;
+            if mat.is_none() {
+                panic!("Material is None");
+            }
+            let mat = mat.unwrap();
+            if mat.is_emissive() {
+                let weight = match (sampling, bsdf_pdf) {
+                    (LightSampling::Nee, Some(_)) => 0.0,
+                    (LightSampling::Mis, Some(pdf)) => {
+                        power_heuristic(pdf, lights.pdf(&ray.origin, &light_normal, &ray.direction))
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput
+                    * mat.emitted_spectrum(rec.u, rec.v, &rec.p, lambdas)
+                    * (mat.emission_falloff(&rec, &-ray.direction) * weight);
+            }
+
+            let mut srec = ScatterRecord::new();
+            if !mat.sample(&ray, &rec, &mut srec) {
+                return (radiance, PathStats::new(bounce + 1, Termination::Absorbed));
+            }
+            if mat.is_dispersive() {
+                // Only the hero wavelength follows the refracted direction
+                lambdas.terminate_secondary();
+            }
+
+            bsdf_pdf = None;
+            if !srec.is_delta && !lights.is_empty() && sampling != LightSampling::Bsdf {
+                if let Some((weight, incoming)) =
+                    ray.light_sample(sampling, &rec, &mat, background, world, lights)
+                {
+                    radiance += throughput
+                        * SampledSpectrum::from_rgb(&weight, lambdas)
+                        * incoming.spectrum(lambdas);
+                }
+                bsdf_pdf = Some(srec.pdf);
+                light_normal = light_normal_at(&rec, &mat);
+            }
+            throughput *= SampledSpectrum::from_rgb(&srec.attenuation, lambdas);
+
+            if bounce + 1 >= settings.rr_depth {
+                let survival = throughput.max_value().min(0.95);
+                if rng.gen_range(0.0..1.0) >= survival {
+                    return (
+                        radiance,
+                        PathStats::new(bounce + 1, Termination::RussianRoulette),
+                    );
+                }
+                throughput = throughput / survival;
+            }
+
+            ray = srec.scattered;
         }
-        let mat = mat.unwrap();
 
-        let emitted = mat.emitted(rec.u, rec.v, &rec.p);
-        let mut scattered = Self::new(&rec.p, &rec.normal, 0.0); // Temporary Ray
-        let mut attenuation = vec3::Color::zero();
+        (
+            radiance,
+            PathStats::new(settings.max_depth, Termination::MaxDepth),
+        )
+    }
+}
 
-        if !mat.scatter(self, &rec, &mut attenuation, &mut scattered) {
-            return emitted;
+/// Light reached by a light sample, kept apart from the weight of the sample so spectral
+/// paths can take it as an illuminant
+enum Incoming {
+    /// Radiance from a sampled light or the background
+    Light(vec3::Color),
+    /// The hit on an emissive surface, seen from the direction given
+    Surface(Box<hittable::HitRecord>, vec3::Vec3),
+}
+
+impl Incoming {
+    fn rgb(&self) -> vec3::Color {
+        match self {
+            Incoming::Light(radiance) => *radiance,
+            Incoming::Surface(rec, wo) => {
+                let mat = rec.material.as_ref().expect("Material is None");
+                mat.emitted(rec.u, rec.v, &rec.p) * mat.emission_falloff(rec, wo)
+            }
+        }
+    }
+
+    fn spectrum(&self, lambdas: &SampledWavelengths) -> SampledSpectrum {
+        match self {
+            Incoming::Light(radiance) => SampledSpectrum::from_rgb_illuminant(radiance, lambdas),
+            Incoming::Surface(rec, wo) => {
+                let mat = rec.material.as_ref().expect("Material is None");
+                mat.emitted_spectrum(rec.u, rec.v, &rec.p, lambdas) * mat.emission_falloff(rec, wo)
+            }
         }
+    }
+}
 
-        emitted + attenuation * scattered.color(background, world, depth - 1)
+/// Normal to pick lights for at `rec`, zero in volumes where light arrives from all around
+pub(crate) fn light_normal_at(rec: &hittable::HitRecord, mat: &MaterialEnum) -> vec3::Vec3 {
+    match mat {
+        MaterialEnum::Isotropic(_) => vec3::Vec3::zero(),
+        // Light only goes through subsurface materials
+        MaterialEnum::Subsurface(_) => -rec.normal,
+        // Light from either side if the parts disagree
+        MaterialEnum::Mix(m) => {
+            let first = light_normal_at(rec, m.first());
+            if first == light_normal_at(rec, m.second()) {
+                first
+            } else {
+                vec3::Vec3::zero()
+            }
+        }
+        _ => rec.normal,
     }
 }
@@ -1,9 +1,23 @@
+pub mod conductors;
 pub mod cornell_box;
+pub mod cornell_ies;
 pub mod cornell_smoke;
+pub mod cornell_subsurface;
 pub mod earthball;
 pub mod final_scene;
+pub mod fireball;
+pub mod frosted_glass;
+pub mod glass_of_water;
+pub mod layered;
+pub mod many_lights;
+pub mod principled;
+pub mod prism;
+pub mod punctual_lights;
 pub mod random;
 pub mod random_moving;
 pub mod simple_light;
+pub mod thin_film;
+pub mod tinted_glass;
 pub mod two_checker_spheres;
 pub mod two_perlin_spheres;
+pub mod veach_mis;
@@ -0,0 +1,103 @@
+use crate::hittable::{BvhNode, HittableEnum, HittableList, Sphere, XYRect, XZRect, YZRect};
+use crate::material::{Conductor, DiffuseLight, Lambertian, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(15.0, 15.0, 15.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        green.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        red.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(110.0, 80.0, 200.0),
+        80.0,
+        MaterialEnum::Conductor(Conductor::gold(0.2)),
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(278.0, 80.0, 300.0),
+        80.0,
+        MaterialEnum::Conductor(Conductor::copper(0.4)),
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(445.0, 80.0, 200.0),
+        80.0,
+        MaterialEnum::Conductor(Conductor::aluminum(0.0)),
+    )));
+    // Aluminum brushed around the vertical axis, with highlights stretched across it
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(278.0, 260.0, 380.0),
+        70.0,
+        MaterialEnum::Conductor(Conductor::new(
+            &Color::new(1.657, 0.880, 0.521),
+            &Color::new(9.224, 6.270, 4.837),
+            0.1,
+            0.6,
+        )),
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,107 @@
+use crate::hittable::{
+    BvhNode, Cuboid, HittableEnum, HittableList, RotateX, RotateY, Translation, XYRect, XZRect,
+    YZRect,
+};
+use crate::light::IesProfile;
+use crate::material::{DiffuseLight, Lambertian, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Asymmetric wall washer: throws most of its light forwards and up to 50 degrees off
+/// the nadir, little sideways, and almost none backwards
+const WALL_WASHER: &str = "IESNA:LM-63-2002
+[TEST] Synthetic wall washer
+[MANUFAC] ray-tracer-rs
+TILT=NONE
+1 1000 1.0 10 3 1 2 0.1 0.1 0.0
+1.0 1.0 20
+0 10 20 30 40 50 60 70 80 90
+0 90 180
+400 600 900 1300 1600 1700 1400 800 250 0
+400 450 450 400 330 250 160 80 20 0
+400 300 200 120 60 25 10 5 0 0
+";
+
+/// The Cornell box lit by three ceiling wall washers aimed at the back wall, which
+/// draw scallops of light on it. Seen from the usual Cornell box camera.
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let profile: IesProfile = WALL_WASHER.parse().unwrap();
+    let washer = MaterialEnum::DiffuseLight(
+        DiffuseLight::new(&TextureEnum::SolidColor(SolidColor::new(Color::new(
+            100.0, 95.0, 85.0,
+        ))))
+        .with_profile(&profile, &Vec3::new(0.0, 0.0, 1.0)),
+    );
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0, 555.0, 0.0, 555.0, 555.0, green,
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0, 555.0, 0.0, 555.0, 0.0, red,
+    )));
+    // Only the front of a profiled light emits. Rects face up, so the washers are turned
+    // upside down to face the room.
+    for x in [100.0, 257.5, 415.0] {
+        world.push(HittableEnum::RotateX(Box::new(RotateX::new(
+            HittableEnum::XZRect(XZRect::new(
+                x - 20.0,
+                x + 20.0,
+                -470.0,
+                -430.0,
+                -554.0,
+                washer.clone(),
+            )),
+            180.0,
+        ))));
+    }
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    let cuboid = HittableEnum::Cuboid(Cuboid::new(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(165.0, 165.0, 165.0),
+        white,
+    ));
+    let cuboid = HittableEnum::RotateY(Box::new(RotateY::new(cuboid, -18.0)));
+    let cuboid = HittableEnum::Translation(Translation::new(cuboid, Vec3::new(130.0, 0.0, 65.0)));
+    world.push(cuboid);
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,107 @@
+use crate::hittable::{
+    BvhNode, Cuboid, HittableEnum, HittableList, RotateY, Sphere, Translation, XYRect, XZRect,
+    YZRect,
+};
+use crate::material::{DiffuseLight, Lambertian, MaterialEnum, Subsurface};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+/// The Cornell box with a tall block of marble and a ball of wax, lit through their
+/// surfaces
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(15.0, 15.0, 15.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        green.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        red.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    let marble = MaterialEnum::Subsurface(Subsurface::new(
+        &Color::new(0.99, 0.98, 0.97),
+        &Color::new(40.0, 35.0, 30.0),
+    ));
+    let cuboid = HittableEnum::Cuboid(Cuboid::new(
+        &crate::vec3::Point3::new(0.0, 0.0, 0.0),
+        &crate::vec3::Point3::new(165.0, 330.0, 165.0),
+        marble,
+    ));
+    let cuboid = HittableEnum::RotateY(Box::new(RotateY::new(cuboid, 15.0)));
+    let cuboid = HittableEnum::Translation(Translation::new(
+        cuboid,
+        crate::vec3::Vec3::new(265.0, 0.0, 295.0),
+    ));
+    world.push(cuboid);
+
+    // Red light travels furthest through wax
+    let wax = MaterialEnum::Subsurface(Subsurface::new(
+        &Color::new(0.99, 0.95, 0.8),
+        &Color::new(40.0, 20.0, 10.0),
+    ));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(190.0, 90.0, 190.0),
+        90.0,
+        wax,
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -76,7 +76,7 @@ pub fn scene() -> HittableEnum {
     hlist.add(HittableEnum::Sphere(Sphere::new(
         &Point3::new(0.0, 150.0, 145.0),
         50.0,
-        MaterialEnum::Metal(Metal::new(&Color::new(0.8, 0.8, 0.9), 10.0)),
+        MaterialEnum::Metal(Metal::new(&Color::new(0.8, 0.8, 0.9), 1.0)),
     )));
 
     let boundary = HittableEnum::Sphere(Sphere::new(
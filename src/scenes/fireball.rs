@@ -0,0 +1,58 @@
+use crate::hittable::{
+    BvhNode, ConstantMedium, Grid, HittableEnum, HittableList, MediumEmission, Sphere, XZRect,
+};
+use crate::material::{Isotropic, Lambertian, MaterialEnum};
+use crate::texture::noise::perlin::Perlin;
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+/// A ball of fire over a plain floor, lit by nothing but its own glow: a sooty medium
+/// whose temperature falls off from a turbulent core. Meant to be seen from (0, 3, 14)
+/// towards (0, 2.5, 0).
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let floor = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.5, 0.5, 0.5)),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        -50.0, 50.0, -50.0, 50.0, 0.0, floor,
+    )));
+
+    let center = Point3::new(0.0, 3.0, 0.0);
+    let radius = 2.5;
+    let noise = Perlin::new();
+    let temperature = Grid::from_fn(
+        center - Point3::new(radius, radius, radius),
+        center + Point3::new(radius, radius, radius),
+        48,
+        48,
+        48,
+        |p| {
+            let r = (*p - center).len() / radius;
+            let heat = 1.1 - r + 0.8 * noise.turb(&(*p * 1.5), 7) - 0.3;
+            2000.0 * heat.clamp(0.0, 1.0)
+        },
+    );
+    let boundary = HittableEnum::Sphere(Sphere::new(
+        &center,
+        radius,
+        MaterialEnum::Isotropic(Isotropic::new(&TextureEnum::SolidColor(SolidColor::new(
+            Color::new(1.0, 1.0, 1.0),
+        )))),
+    ));
+    world.push(HittableEnum::ConstantMedium(Box::new(
+        ConstantMedium::new(
+            boundary,
+            0.2,
+            TextureEnum::SolidColor(SolidColor::new(Color::new(0.2, 0.2, 0.2))),
+        )
+        .with_emission(MediumEmission::Blackbody(temperature, 1.0)),
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,105 @@
+use crate::hittable::{
+    BvhNode, Cuboid, HittableEnum, HittableList, RotateY, Sphere, Translation, XYRect, XZRect,
+    YZRect,
+};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, MaterialEnum, RoughDielectric};
+use crate::texture::{NoiseTexture, SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3, Vec3};
+
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(15.0, 15.0, 15.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        green.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        red.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    // Smooth and frosted glass side by side
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(130.0, 90.0, 250.0),
+        90.0,
+        MaterialEnum::Dielectric(Dielectric::new(1.5)),
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(330.0, 90.0, 250.0),
+        90.0,
+        MaterialEnum::RoughDielectric(RoughDielectric::new(Dielectric::new(1.5), 0.3)),
+    )));
+
+    // Translucent ice with a slight blue tint and uneven frosting
+    let ice = RoughDielectric::with_roughness_texture(
+        Dielectric::new(1.31).with_absorption(&Color::new(0.8, 0.9, 0.95), 100.0),
+        TextureEnum::NoiseTexture(NoiseTexture::new(0.05)),
+    );
+    let cube = HittableEnum::Cuboid(Cuboid::new(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(110.0, 110.0, 110.0),
+        MaterialEnum::RoughDielectric(ice),
+    ));
+    let cube = HittableEnum::RotateY(Box::new(RotateY::new(cube, 20.0)));
+    let cube = HittableEnum::Translation(Translation::new(cube, Vec3::new(380.0, 0.0, 60.0)));
+    world.push(cube);
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
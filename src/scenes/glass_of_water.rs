@@ -0,0 +1,128 @@
+use crate::hittable::{
+    BvhNode, Cuboid, HittableEnum, HittableList, RotateY, Translation, XYRect, XZRect, YZRect,
+};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3, Vec3};
+
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(15.0, 15.0, 15.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        green.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        red.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    // The glass wins over the water it overlaps, and the ice wins over the water it floats in
+    let glass = MaterialEnum::Dielectric(Dielectric::new(1.5).with_priority(2));
+    let water = MaterialEnum::Dielectric(Dielectric::new(1.33).with_priority(1));
+    let ice = MaterialEnum::Dielectric(Dielectric::new(1.31).with_priority(3));
+
+    // An open tumbler made of a base and four walls
+    let (x0, x1) = (178.0, 378.0);
+    let (z0, z1) = (178.0, 378.0);
+    let (y0, y1) = (0.0, 300.0);
+    let thickness = 10.0;
+    let panes = [
+        (Point3::new(x0, y0, z0), Point3::new(x1, y0 + thickness, z1)),
+        (
+            Point3::new(x0, y0 + thickness, z0),
+            Point3::new(x0 + thickness, y1, z1),
+        ),
+        (
+            Point3::new(x1 - thickness, y0 + thickness, z0),
+            Point3::new(x1, y1, z1),
+        ),
+        (
+            Point3::new(x0 + thickness, y0 + thickness, z0),
+            Point3::new(x1 - thickness, y1, z0 + thickness),
+        ),
+        (
+            Point3::new(x0 + thickness, y0 + thickness, z1 - thickness),
+            Point3::new(x1 - thickness, y1, z1),
+        ),
+    ];
+    for (p0, p1) in panes.iter() {
+        world.push(HittableEnum::Cuboid(Cuboid::new(p0, p1, glass.clone())));
+    }
+
+    // The water overlaps the panes so that there is no air gap at the water/glass interface
+    let overlap = thickness / 2.0;
+    world.push(HittableEnum::Cuboid(Cuboid::new(
+        &Point3::new(x0 + overlap, y0 + overlap, z0 + overlap),
+        &Point3::new(x1 - overlap, 200.0, z1 - overlap),
+        water.clone(),
+    )));
+
+    let cube = HittableEnum::Cuboid(Cuboid::new(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(70.0, 70.0, 70.0),
+        ice.clone(),
+    ));
+    let cube = HittableEnum::RotateY(Box::new(RotateY::new(cube, 25.0)));
+    let cube = HittableEnum::Translation(Translation::new(cube, Vec3::new(240.0, 140.0, 230.0)));
+    world.push(cube);
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
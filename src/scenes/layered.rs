@@ -0,0 +1,58 @@
+use crate::hittable::{BvhNode, HittableEnum, HittableList, Sphere, XZRect};
+use crate::material::{Coated, DiffuseLight, Lambertian, MaterialEnum, Metal, Mix};
+use crate::texture::{NoiseTexture, SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+/// A row of spheres in blended and coated materials under a large light
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let diffuse = |r: f64, g: f64, b: f64| {
+        MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+            Color::new(r, g, b),
+        ))))
+    };
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        diffuse(0.5, 0.5, 0.5),
+    )));
+
+    let paint = diffuse(0.1, 0.2, 0.6);
+    let rust = diffuse(0.4, 0.15, 0.05);
+    let mask = TextureEnum::NoiseTexture(NoiseTexture::new(4.0));
+    let rusty_paint = MaterialEnum::Mix(Box::new(Mix::new(paint.clone(), rust, mask)));
+    let materials = [
+        // Rust over paint
+        rusty_paint.clone(),
+        // Car paint under a clear coat
+        MaterialEnum::Coated(Coated::new(paint, 1.5)),
+        // Varnish over the rusty paint
+        MaterialEnum::Coated(Coated::new(rusty_paint, 1.5)),
+        // Lacquered brass
+        MaterialEnum::Coated(Coated::new(
+            MaterialEnum::Metal(Metal::new(&Color::new(0.9, 0.7, 0.3), 0.3)),
+            1.5,
+        )),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.push(HittableEnum::Sphere(Sphere::new(
+            &Point3::new(0.0, 1.0, 3.3 - 2.2 * i as f64),
+            1.0,
+            material,
+        )));
+    }
+
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(4.0, 4.0, 4.0)),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        -2.0, 4.0, -4.0, 4.0, 6.0, light,
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
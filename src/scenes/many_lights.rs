@@ -0,0 +1,82 @@
+use crate::hittable::{BvhNode, Cuboid, HittableEnum, HittableList, Sphere, XZRect};
+use crate::material::{DiffuseLight, Lambertian, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+use rand::Rng;
+
+fn lamp(color: Color) -> MaterialEnum {
+    MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(color),
+    )))
+}
+
+/// A night-time city block grid lit by about two thousand small lights: street lamps
+/// along every street and coloured lanterns strung above them. Stresses the light BVH.
+/// Meant to be seen from (0, 10, 26) towards (0, 0, 0).
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+    let mut rng = rand::thread_rng();
+
+    let ground = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.4, 0.4, 0.4)),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        -40.0, 40.0, -40.0, 40.0, 0.0, ground,
+    )));
+
+    // Blocks of 4 by 4 with streets of 2 in between
+    let concrete = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.6, 0.58, 0.55)),
+    )));
+    for i in -3..3 {
+        for j in -3..3 {
+            let x = 6.0 * i as f64 + 1.0;
+            let z = 6.0 * j as f64 + 1.0;
+            let height = rng.gen_range(1.5..7.0);
+            world.push(HittableEnum::Cuboid(Cuboid::new(
+                &Point3::new(x, 0.0, z),
+                &Point3::new(x + 4.0, height, z + 4.0),
+                concrete.clone(),
+            )));
+        }
+    }
+
+    // Street lamps every unit along both directions of each street
+    let sodium = lamp(Color::new(60.0, 35.0, 10.0));
+    for street in -3..4 {
+        let s = 6.0 * street as f64;
+        for step in 0..36 {
+            let t = -18.0 + step as f64 + 0.5;
+            for center in [Point3::new(s, 1.2, t), Point3::new(t, 1.2, s)] {
+                world.push(HittableEnum::Sphere(Sphere::new(
+                    &center,
+                    0.04,
+                    sodium.clone(),
+                )));
+            }
+        }
+    }
+
+    // Lanterns over the streets
+    for _ in 0..1500 {
+        let street = 6.0 * rng.gen_range(-3..4) as f64 + rng.gen_range(-0.6..0.6);
+        let along = rng.gen_range(-18.0..18.0);
+        let center = if rng.gen_bool(0.5) {
+            Point3::new(street, rng.gen_range(2.0..4.0), along)
+        } else {
+            Point3::new(along, rng.gen_range(2.0..4.0), street)
+        };
+        let color = Color::rand_range(0.2, 1.0) * 30.0;
+        world.push(HittableEnum::Sphere(Sphere::new(
+            &center,
+            0.02,
+            lamp(color),
+        )));
+    }
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,109 @@
+use crate::hittable::{BvhNode, HittableEnum, HittableList, Sphere, XYRect, XZRect, YZRect};
+use crate::material::{DiffuseLight, Lambertian, MaterialEnum, Principled};
+use crate::texture::{Checker, SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(15.0, 15.0, 15.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        green.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        red.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    let solid =
+        |r: f64, g: f64, b: f64| TextureEnum::SolidColor(SolidColor::new(Color::new(r, g, b)));
+    let materials = [
+        // Glossy plastic
+        Principled::new(&solid(0.8, 0.1, 0.1)).with_roughness(0.3),
+        // Brushed brass-like metal
+        Principled::new(&solid(0.9, 0.7, 0.3))
+            .with_metallic(1.0)
+            .with_roughness(0.4),
+        // Car paint: diffuse base under a glossy clearcoat
+        Principled::new(&solid(0.1, 0.2, 0.6))
+            .with_roughness(0.6)
+            .with_clearcoat(1.0, 0.9),
+        // Velvet-like cloth with a checkered roughness
+        Principled::new(&solid(0.5, 0.1, 0.4))
+            .with_roughness(TextureEnum::Checker(Checker::new(
+                solid(1.0, 1.0, 1.0),
+                solid(0.3, 0.3, 0.3),
+            )))
+            .with_sheen(1.0, 0.5),
+        // Tinted glass
+        Principled::new(&solid(0.8, 1.0, 0.9))
+            .with_roughness(0.05)
+            .with_transmission(1.0, 1.5),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.push(HittableEnum::Sphere(Sphere::new(
+            &Point3::new(70.0 + 104.0 * i as f64, 50.0, 250.0),
+            50.0,
+            MaterialEnum::Principled(Box::new(material)),
+        )));
+    }
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
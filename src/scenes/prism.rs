@@ -0,0 +1,91 @@
+use crate::hittable::{
+    BvhNode, Cuboid, HittableEnum, HittableList, RotateX, RotateY, Sphere, Translation, XYRect,
+    XZRect, YZRect,
+};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3, Vec3};
+
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    // A small, bright light gives sharp caustics
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(80.0, 80.0, 80.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        253.0,
+        303.0,
+        254.0,
+        304.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    // A tilted cube of flint glass works as a prism between adjacent faces
+    let prism = HittableEnum::Cuboid(Cuboid::new(
+        &Point3::new(-75.0, -75.0, -75.0),
+        &Point3::new(75.0, 75.0, 75.0),
+        MaterialEnum::Dielectric(Dielectric::flint_glass()),
+    ));
+    let prism = HittableEnum::RotateX(Box::new(RotateX::new(prism, 35.0)));
+    let prism = HittableEnum::RotateY(Box::new(RotateY::new(prism, 45.0)));
+    let prism = HittableEnum::Translation(Translation::new(prism, Vec3::new(180.0, 260.0, 300.0)));
+    world.push(prism);
+
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(390.0, 90.0, 220.0),
+        90.0,
+        MaterialEnum::Dielectric(Dielectric::diamond()),
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,73 @@
+use crate::hittable::{BvhNode, HittableEnum, HittableList, Sphere, XZRect};
+use crate::light::{DirectionalLight, LightEnum, PointLight, SpotLight};
+use crate::material::{Lambertian, MaterialEnum, Metal};
+use crate::texture::{Checker, SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Spheres on a checkered floor lit only by lights without geometry, see `lights`.
+/// Meant to be seen from (13, 2, 3) towards the origin.
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let checker = TextureEnum::Checker(Checker::new(
+        TextureEnum::SolidColor(SolidColor::new(Color::new(0.2, 0.3, 0.1))),
+        TextureEnum::SolidColor(SolidColor::new(Color::new(0.9, 0.9, 0.9))),
+    ));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        -20.0,
+        20.0,
+        -20.0,
+        20.0,
+        0.0,
+        MaterialEnum::Lambertian(Lambertian::new(&checker)),
+    )));
+
+    let diffuse = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.7, 0.3, 0.2)),
+    )));
+    let metal = MaterialEnum::Metal(Metal::new(&Color::new(0.8, 0.8, 0.8), 0.1));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(0.0, 1.0, -2.0),
+        1.0,
+        diffuse.clone(),
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(0.0, 1.0, 0.5),
+        1.0,
+        metal,
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(2.0, 0.5, 2.5),
+        0.5,
+        diffuse,
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
+
+/// A soft point light, a spotlight on the front sphere and a dim low sun
+pub fn lights() -> Vec<LightEnum> {
+    vec![
+        LightEnum::Point(
+            PointLight::new(&Point3::new(-3.0, 5.0, -4.0), &Color::new(20.0, 18.0, 15.0))
+                .with_radius(0.3),
+        ),
+        LightEnum::Spot(
+            SpotLight::new(
+                &Point3::new(6.0, 6.0, 2.0),
+                &Vec3::new(-4.0, -5.5, 0.5),
+                &Color::new(60.0, 60.0, 80.0),
+                20.0,
+            )
+            .with_falloff_start(12.0),
+        ),
+        LightEnum::Directional(
+            DirectionalLight::new(&Vec3::new(1.0, -0.5, -1.0), &Color::new(0.3, 0.25, 0.2))
+                .with_angular_diameter(2.0),
+        ),
+    ]
+}
@@ -0,0 +1,52 @@
+use crate::hittable::{BvhNode, HittableEnum, HittableList, Sphere, XZRect};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, MaterialEnum, Metal, ThinFilm};
+use crate::texture::{NoiseTexture, SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+/// Spheres with iridescent films under a large light
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+            Color::new(0.5, 0.5, 0.5),
+        )))),
+    )));
+
+    let swirls = TextureEnum::NoiseTexture(NoiseTexture::new(4.0));
+    let materials = [
+        // Soap bubble: a film of water around air, thinning in swirls
+        MaterialEnum::Dielectric(Dielectric::new(1.0).with_thin_film(
+            ThinFilm::with_thickness_texture(swirls.clone(), 200.0, 800.0, 1.33),
+        )),
+        // Glass with a quarter-wave anti-reflection coating of magnesium fluoride
+        MaterialEnum::Dielectric(Dielectric::new(1.5).with_thin_film(ThinFilm::new(100.0, 1.38))),
+        // Heat-tinted steel, its oxide layer a few tens of nanometres thick
+        MaterialEnum::Metal(
+            Metal::new(&Color::new(0.6, 0.6, 0.6), 0.0)
+                .with_thin_film(ThinFilm::with_thickness_texture(swirls, 20.0, 150.0, 2.5)),
+        ),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.push(HittableEnum::Sphere(Sphere::new(
+            &Point3::new(0.0, 1.0, 2.2 - 2.2 * i as f64),
+            1.0,
+            material,
+        )));
+    }
+
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(4.0, 4.0, 4.0)),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        -2.0, 4.0, -4.0, 4.0, 6.0, light,
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,107 @@
+use crate::hittable::{
+    BvhNode, Cuboid, HittableEnum, HittableList, Sphere, XYRect, XZRect, YZRect,
+};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3};
+
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+
+    let red = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(SolidColor::new(
+        Color::new(0.65, 0.05, 0.05),
+    ))));
+    let white = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.73, 0.73, 0.73)),
+    )));
+    let green = MaterialEnum::Lambertian(Lambertian::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(0.12, 0.45, 0.15)),
+    )));
+    let light = MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+        SolidColor::new(Color::new(15.0, 15.0, 15.0)),
+    )));
+
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        green.clone(),
+    )));
+    world.push(HittableEnum::YZRect(YZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        red.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        light.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XZRect(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.push(HittableEnum::XYRect(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    // The same bottle-green glass looks deeper the more of it the light passes through
+    let tint = Color::new(0.35, 0.75, 0.45);
+    let glass = MaterialEnum::Dielectric(Dielectric::tinted_glass(&tint, 100.0));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(120.0, 50.0, 200.0),
+        50.0,
+        glass.clone(),
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(260.0, 80.0, 260.0),
+        80.0,
+        glass.clone(),
+    )));
+    world.push(HittableEnum::Sphere(Sphere::new(
+        &Point3::new(420.0, 110.0, 330.0),
+        110.0,
+        glass.clone(),
+    )));
+
+    // A slab of deep water: red is absorbed within a short distance
+    let water = MaterialEnum::Dielectric(
+        Dielectric::new(1.33).with_absorption(&Color::new(0.3, 0.85, 0.95), 150.0),
+    );
+    world.push(HittableEnum::Cuboid(Cuboid::new(
+        &Point3::new(60.0, 0.0, 400.0),
+        &Point3::new(300.0, 60.0, 520.0),
+        water,
+    )));
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,60 @@
+use crate::hittable::{BvhNode, HittableEnum, HittableList, RotateX, Sphere, Translation, XZRect};
+use crate::material::{Conductor, DiffuseLight, MaterialEnum};
+use crate::texture::{SolidColor, TextureEnum};
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Veach's multiple importance sampling test: four plates from glossy to rough reflect
+/// four lights of equal power, from small and bright to large and dim.
+/// Light sampling alone is noisy on the glossy plates, BSDF sampling on the small lights.
+/// Meant to be seen from (0, 2, 15) towards the origin.
+pub fn scene() -> HittableEnum {
+    let mut world: Vec<HittableEnum> = Vec::new();
+    let camera = Point3::new(0.0, 2.0, 15.0);
+
+    let light_y = 4.0;
+    let light_z = -4.0;
+    for (i, radius) in [0.03, 0.1, 0.3, 0.9].iter().enumerate() {
+        let radiance = 0.8 / (radius * radius);
+        world.push(HittableEnum::Sphere(Sphere::new(
+            &Point3::new(-3.75 + 2.5 * i as f64, light_y, light_z),
+            *radius,
+            MaterialEnum::DiffuseLight(DiffuseLight::new(&TextureEnum::SolidColor(
+                SolidColor::new(Color::new(radiance, radiance, radiance)),
+            ))),
+        )));
+    }
+
+    // Each plate is tilted to mirror the middle of the light row into the camera
+    let plates = [
+        (0.05, Point3::new(0.0, -3.4, 4.0)),
+        (0.12, Point3::new(0.0, -2.0, 1.5)),
+        (0.25, Point3::new(0.0, -0.7, -1.0)),
+        (0.45, Point3::new(0.0, 0.5, -3.5)),
+    ];
+    for (roughness, center) in plates.iter() {
+        let to_camera = (camera - *center).unit();
+        let to_light = (Point3::new(0.0, light_y, light_z) - *center).unit();
+        let normal = (to_camera + to_light).unit();
+        let angle = normal.z().atan2(normal.y()).to_degrees();
+
+        let plate = HittableEnum::XZRect(XZRect::new(
+            -5.0,
+            5.0,
+            -0.8,
+            0.8,
+            0.0,
+            MaterialEnum::Conductor(Conductor::aluminum(*roughness)),
+        ));
+        let plate = HittableEnum::RotateX(Box::new(RotateX::new(plate, angle)));
+        world.push(HittableEnum::Translation(Translation::new(
+            plate,
+            Vec3::new(center.x(), center.y(), center.z()),
+        )));
+    }
+
+    let bvh = HittableEnum::BvhNode(Box::new(BvhNode::new(&mut world, 0.0, 0.0)));
+
+    let mut world = HittableList::new();
+    world.add(bvh);
+    HittableEnum::HittableList(Box::new(world))
+}
@@ -0,0 +1,139 @@
+use crate::random;
+use crate::vec3::{Color, Vec3};
+use rand::Rng;
+use std::sync::OnceLock;
+
+pub mod blackbody;
+pub use blackbody::Blackbody;
+
+pub mod sampled_spectrum;
+pub use sampled_spectrum::{SampledSpectrum, SampledWavelengths, N_SPECTRUM_SAMPLES};
+
+mod smits;
+
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 780.0;
+
+fn piecewise_gaussian(x: f64, mu: f64, sigma1: f64, sigma2: f64) -> f64 {
+    let t = (x - mu) / if x < mu { sigma1 } else { sigma2 };
+    (-0.5 * t * t).exp()
+}
+
+/// CIE 1931 colour matching functions (multi-lobe fit by Wyman, Sloan and Shirley)
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    let x = 1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+/// Relative luminance (CIE Y) of a linear sRGB colour
+pub fn luminance(c: &Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
+
+/// CIE XYZ to linear sRGB
+pub fn xyz_to_rgb(xyz: &Vec3) -> Color {
+    Color::new(
+        3.2404542 * xyz.x() - 1.5371385 * xyz.y() - 0.4985314 * xyz.z(),
+        -0.9692660 * xyz.x() + 1.8760108 * xyz.y() + 0.0415560 * xyz.z(),
+        0.0556434 * xyz.x() - 0.2040259 * xyz.y() + 1.0572252 * xyz.z(),
+    )
+}
+
+fn clamped_rgb(lambda: f64) -> Color {
+    let rgb = xyz_to_rgb(&cie_xyz(lambda));
+    Color::new(rgb.x().max(0.0), rgb.y().max(0.0), rgb.z().max(0.0))
+}
+
+/// Average of `f` over the visible range
+fn visible_average(f: impl Fn(f64) -> Color) -> Color {
+    let steps = 400;
+    let mut sum = Color::zero();
+    for i in 0..steps {
+        let lambda = LAMBDA_MIN + (i as f64 + 0.5) / steps as f64 * (LAMBDA_MAX - LAMBDA_MIN);
+        sum += f(lambda);
+    }
+    sum / steps as f64
+}
+
+fn balance(rgb: &Color, white: &Color) -> Color {
+    Color::new(
+        rgb.x() / white.x(),
+        rgb.y() / white.y(),
+        rgb.z() / white.z(),
+    )
+}
+
+/// Average of `clamped_rgb` over the visible range, so that white stays white
+fn white_balance() -> &'static Color {
+    static WHITE: OnceLock<Color> = OnceLock::new();
+    WHITE.get_or_init(|| visible_average(clamped_rgb))
+}
+
+/// Linear RGB of the equal-energy spectrum, which is mapped to white
+fn equal_energy_rgb() -> &'static Color {
+    static WHITE: OnceLock<Color> = OnceLock::new();
+    WHITE.get_or_init(|| visible_average(|lambda| xyz_to_rgb(&cie_xyz(lambda))))
+}
+
+/// Linear RGB of a spectral distribution given as a function of wavelength
+pub fn spectrum_function_to_rgb(f: impl Fn(f64) -> f64) -> Color {
+    let rgb = visible_average(|lambda| f(lambda) * xyz_to_rgb(&cie_xyz(lambda)));
+    balance(&rgb, equal_energy_rgb())
+}
+
+/// Wavelengths `reflectance_to_rgb` takes, apart
+const REFLECTANCE_STEP: f64 = 10.0;
+
+/// Linear RGB of a reflectance spectrum lit by white light, with 1 everywhere giving
+/// white. Coarser than `spectrum_function_to_rgb`, but quick enough to call at every hit.
+pub fn reflectance_to_rgb(f: impl Fn(f64) -> f64) -> Color {
+    static WEIGHTS: OnceLock<Vec<(f64, Color)>> = OnceLock::new();
+    let weights = WEIGHTS.get_or_init(|| {
+        let steps = ((LAMBDA_MAX - LAMBDA_MIN) / REFLECTANCE_STEP) as usize;
+        let weights: Vec<(f64, Color)> = (0..steps)
+            .map(|i| {
+                let lambda = LAMBDA_MIN + (i as f64 + 0.5) * REFLECTANCE_STEP;
+                (lambda, xyz_to_rgb(&cie_xyz(lambda)))
+            })
+            .collect();
+        let white = weights
+            .iter()
+            .fold(Color::zero(), |sum, (_, rgb)| sum + *rgb);
+        weights
+            .into_iter()
+            .map(|(lambda, rgb)| (lambda, balance(&rgb, &white)))
+            .collect()
+    });
+    weights
+        .iter()
+        .fold(Color::zero(), |sum, (lambda, rgb)| sum + *rgb * f(*lambda))
+}
+
+/// Monte Carlo estimate of the linear RGB of a sampled spectrum,
+/// going through CIE XYZ for the wavelengths still alive
+pub fn to_rgb(s: &SampledSpectrum, lambdas: &SampledWavelengths) -> Color {
+    let mut xyz = Vec3::zero();
+    for i in 0..N_SPECTRUM_SAMPLES {
+        if lambdas.pdf(i) > 0.0 {
+            xyz += s[i] / lambdas.pdf(i) * cie_xyz(lambdas.lambda(i));
+        }
+    }
+    let rgb = xyz_to_rgb(&xyz) / (N_SPECTRUM_SAMPLES as f64 * (LAMBDA_MAX - LAMBDA_MIN));
+    balance(&rgb, equal_energy_rgb())
+}
+
+pub fn sample_wavelength() -> f64 {
+    random::rng().gen_range(LAMBDA_MIN..LAMBDA_MAX)
+}
+
+/// RGB weight of a path carrying only `lambda`, for wavelengths sampled uniformly
+/// by `sample_wavelength`. Averages to white over all wavelengths.
+pub fn wavelength_to_rgb(lambda: f64) -> Color {
+    balance(&clamped_rgb(lambda), white_balance())
+}
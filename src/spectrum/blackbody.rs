@@ -0,0 +1,47 @@
+use super::{spectrum_function_to_rgb, SampledSpectrum, SampledWavelengths};
+use crate::vec3::Color;
+use serde::{Deserialize, Serialize};
+
+/// Planck's law: spectral radiance of a blackbody at `temperature` (K) for `lambda` in nm
+pub fn planck(lambda: f64, temperature: f64) -> f64 {
+    if temperature <= 0.0 {
+        return 0.0;
+    }
+    let c = 299792458.0;
+    let h = 6.62606957e-34;
+    let kb = 1.3806488e-23;
+    let l = lambda * 1e-9;
+    (2.0 * h * c * c) / (l.powi(5) * (((h * c) / (l * kb * temperature)).exp() - 1.0))
+}
+
+/// Blackbody emission normalized to 1 at its peak wavelength, then scaled by `intensity`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Blackbody {
+    temperature: f64,
+    intensity: f64,
+    normalization: f64,
+}
+
+impl Blackbody {
+    pub fn new(temperature: f64, intensity: f64) -> Self {
+        // Wien's displacement law
+        let lambda_max = 2.8977721e-3 / temperature * 1e9;
+        Self {
+            temperature,
+            intensity,
+            normalization: 1.0 / planck(lambda_max, temperature),
+        }
+    }
+
+    pub fn value(&self, lambda: f64) -> f64 {
+        self.intensity * planck(lambda, self.temperature) * self.normalization
+    }
+
+    pub fn sample(&self, lambdas: &SampledWavelengths) -> SampledSpectrum {
+        SampledSpectrum::from_fn(|lambda| self.value(lambda), lambdas)
+    }
+
+    pub fn rgb(&self) -> Color {
+        spectrum_function_to_rgb(|lambda| self.value(lambda))
+    }
+}
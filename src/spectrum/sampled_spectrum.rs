@@ -0,0 +1,174 @@
+use super::{LAMBDA_MAX, LAMBDA_MIN};
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign};
+
+pub const N_SPECTRUM_SAMPLES: usize = 4;
+
+/// Wavelengths (in nm) carried by a path. The first one is the hero wavelength,
+/// the others are spread evenly over the visible range from it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct SampledWavelengths {
+    lambda: [f64; N_SPECTRUM_SAMPLES],
+    pdf: [f64; N_SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    pub fn sample_uniform(u: f64) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let mut lambda = [0.0; N_SPECTRUM_SAMPLES];
+        lambda[0] = LAMBDA_MIN + u * range;
+        let delta = range / N_SPECTRUM_SAMPLES as f64;
+        for i in 1..N_SPECTRUM_SAMPLES {
+            lambda[i] = lambda[i - 1] + delta;
+            if lambda[i] > LAMBDA_MAX {
+                lambda[i] -= range;
+            }
+        }
+        Self {
+            lambda,
+            pdf: [1.0 / range; N_SPECTRUM_SAMPLES],
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self, i: usize) -> f64 {
+        self.lambda[i]
+    }
+
+    pub fn pdf(&self, i: usize) -> f64 {
+        self.pdf[i]
+    }
+
+    /// Keeps only the hero wavelength, e.g. after a wavelength-dependent refraction
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        for i in 1..N_SPECTRUM_SAMPLES {
+            self.pdf[i] = 0.0;
+        }
+        self.pdf[0] /= N_SPECTRUM_SAMPLES as f64;
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+}
+
+/// Values of a spectral quantity at the wavelengths of a `SampledWavelengths`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SampledSpectrum {
+    values: [f64; N_SPECTRUM_SAMPLES],
+}
+
+impl SampledSpectrum {
+    pub fn new(value: f64) -> Self {
+        Self {
+            values: [value; N_SPECTRUM_SAMPLES],
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0.0)
+    }
+
+    pub fn from_fn(f: impl Fn(f64) -> f64, lambdas: &SampledWavelengths) -> Self {
+        let mut values = [0.0; N_SPECTRUM_SAMPLES];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = f(lambdas.lambda(i));
+        }
+        Self { values }
+    }
+
+    pub fn max_value(&self) -> f64 {
+        self.values
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    pub fn average(&self) -> f64 {
+        self.values.iter().sum::<f64>() / N_SPECTRUM_SAMPLES as f64
+    }
+}
+
+impl Index<usize> for SampledSpectrum {
+    type Output = f64;
+
+    fn index(&self, i: usize) -> &Self::Output {
+        &self.values[i]
+    }
+}
+
+impl IndexMut<usize> for SampledSpectrum {
+    fn index_mut(&mut self, i: usize) -> &mut f64 {
+        &mut self.values[i]
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        let mut values = self.values;
+        for (v, o) in values.iter_mut().zip(other.values.iter()) {
+            *v += o;
+        }
+        Self { values }
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut values = self.values;
+        for (v, o) in values.iter_mut().zip(other.values.iter()) {
+            *v *= o;
+        }
+        Self { values }
+    }
+}
+
+impl MulAssign for SampledSpectrum {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl Mul<f64> for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        let mut values = self.values;
+        for v in values.iter_mut() {
+            *v *= rhs;
+        }
+        Self { values }
+    }
+}
+
+impl Mul<SampledSpectrum> for f64 {
+    type Output = SampledSpectrum;
+
+    fn mul(self, rhs: SampledSpectrum) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Div<f64> for SampledSpectrum {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        self * (1.0 / rhs)
+    }
+}
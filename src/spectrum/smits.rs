@@ -0,0 +1,85 @@
+use super::{SampledSpectrum, SampledWavelengths};
+use crate::vec3::Color;
+
+// Basis spectra from Smits, "An RGB-to-Spectrum Conversion for Reflectances" (1999),
+// sampled in ten bins over 380..720 nm
+const BIN_MIN: f64 = 380.0;
+const BIN_MAX: f64 = 720.0;
+const N_BINS: usize = 10;
+
+const WHITE: [f64; N_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const CYAN: [f64; N_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const MAGENTA: [f64; N_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const YELLOW: [f64; N_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const RED: [f64; N_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const GREEN: [f64; N_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const BLUE: [f64; N_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// Linear interpolation between the bin centres, constant beyond the first and last
+fn lookup(basis: &[f64; N_BINS], lambda: f64) -> f64 {
+    let width = (BIN_MAX - BIN_MIN) / N_BINS as f64;
+    let x = ((lambda - BIN_MIN) / width - 0.5).clamp(0.0, (N_BINS - 1) as f64);
+    let i = (x as usize).min(N_BINS - 2);
+    let t = x - i as f64;
+    (1.0 - t) * basis[i] + t * basis[i + 1]
+}
+
+fn reflectance(rgb: &Color, lambda: f64) -> f64 {
+    let (r, g, b) = (rgb.x(), rgb.y(), rgb.z());
+    let basis = |spectrum: &[f64; N_BINS]| lookup(spectrum, lambda);
+    if r <= g && r <= b {
+        let mut value = r * basis(&WHITE);
+        if g <= b {
+            value += (g - r) * basis(&CYAN) + (b - g) * basis(&BLUE);
+        } else {
+            value += (b - r) * basis(&CYAN) + (g - b) * basis(&GREEN);
+        }
+        value
+    } else if g <= r && g <= b {
+        let mut value = g * basis(&WHITE);
+        if r <= b {
+            value += (r - g) * basis(&MAGENTA) + (b - r) * basis(&BLUE);
+        } else {
+            value += (b - g) * basis(&MAGENTA) + (r - b) * basis(&RED);
+        }
+        value
+    } else {
+        let mut value = b * basis(&WHITE);
+        if r <= g {
+            value += (r - b) * basis(&YELLOW) + (g - r) * basis(&GREEN);
+        } else {
+            value += (g - b) * basis(&YELLOW) + (r - g) * basis(&RED);
+        }
+        value
+    }
+}
+
+impl SampledSpectrum {
+    /// Upsamples a reflectance-like RGB triple
+    pub fn from_rgb(rgb: &Color, lambdas: &SampledWavelengths) -> Self {
+        Self::from_fn(|lambda| reflectance(rgb, lambda).max(0.0), lambdas)
+    }
+
+    /// Upsamples an unbounded RGB triple such as an emission or a background
+    pub fn from_rgb_illuminant(rgb: &Color, lambdas: &SampledWavelengths) -> Self {
+        let scale = rgb.x().max(rgb.y()).max(rgb.z());
+        if scale <= 0.0 {
+            return Self::zero();
+        }
+        Self::from_rgb(&(*rgb / scale), lambdas) * scale
+    }
+}
@@ -1,5 +1,5 @@
 use crate::vec3::{Color, Vec3};
-mod noise;
+pub(crate) mod noise;
 
 pub mod solid_color;
 pub use solid_color::SolidColor;
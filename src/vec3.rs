@@ -1,4 +1,5 @@
 use crate::clamp;
+use crate::random;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -6,7 +7,7 @@ use std::{
     ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct Vec3 {
     e: [f64; 3],
 }
@@ -74,17 +75,17 @@ impl Vec3 {
 
     pub fn rand() -> Self {
         Self::new(
-            rand::thread_rng().gen_range(0.0..1.0),
-            rand::thread_rng().gen_range(0.0..1.0),
-            rand::thread_rng().gen_range(0.0..1.0),
+            random::rng().gen_range(0.0..1.0),
+            random::rng().gen_range(0.0..1.0),
+            random::rng().gen_range(0.0..1.0),
         )
     }
 
     pub fn rand_range(min: f64, max: f64) -> Self {
         Self::new(
-            rand::thread_rng().gen_range(min..max),
-            rand::thread_rng().gen_range(min..max),
-            rand::thread_rng().gen_range(min..max),
+            random::rng().gen_range(min..max),
+            random::rng().gen_range(min..max),
+            random::rng().gen_range(min..max),
         )
     }
 
@@ -99,19 +100,19 @@ impl Vec3 {
     }
 
     pub fn rand_unit_vector() -> Self {
-        let a = rand::thread_rng().gen_range(0.0..2.0 * std::f64::consts::PI);
-        let z = rand::thread_rng().gen_range(-1.0..1.0);
-        let r = ((1.0 - z * z) as f64).sqrt();
+        let a = random::rng().gen_range(0.0..2.0 * std::f64::consts::PI);
+        let z: f64 = random::rng().gen_range(-1.0..1.0);
+        let r = (1.0 - z * z).sqrt();
         Self::new(r * a.cos(), r * a.sin(), z)
     }
 
     pub fn rand_in_unit_disk() -> Self {
-        let mut x = rand::thread_rng().gen_range(-1.0..1.0);
-        let mut y = rand::thread_rng().gen_range(-1.0..1.0);
+        let mut x = random::rng().gen_range(-1.0..1.0);
+        let mut y = random::rng().gen_range(-1.0..1.0);
         loop {
             if x * x + y * y >= 1.0 {
-                x = rand::thread_rng().gen_range(-1.0..1.0);
-                y = rand::thread_rng().gen_range(-1.0..1.0);
+                x = random::rng().gen_range(-1.0..1.0);
+                y = random::rng().gen_range(-1.0..1.0);
             } else {
                 break;
             }